@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use amethyst::config::Config;
+use amethyst::ecs::Entity;
+use amethyst::utils::application_root_dir;
+use serde::{Deserialize, Serialize};
+
+use crate::sprites::{SpriteNames, SpriteResolver};
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ClipEvent {
+    pub frame: usize,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ClipData {
+    pub range: String,
+    pub frame_time: f32,
+    #[serde(default)]
+    pub durations: Vec<f32>, // per frame overrides of frame_time
+    pub looping: bool,
+    #[serde(default)]
+    pub events: Vec<ClipEvent>,
+}
+
+// Published on an EventChannel when a frame with events starts
+#[derive(Debug, Clone)]
+pub struct AnimationEvent {
+    pub entity: Entity,
+    pub name: String,
+}
+
+// What has to hold for a transition to fire
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum Condition {
+    Airborne,
+    Landed,
+    Moving, // on the ground with horizontal velocity
+    Idle, // on the ground standing still
+    Finished, // a one shot clip played its last frame
+    Trigger(String), // requested by a system with SpriteAnimation::trigger
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Transition {
+    pub from: Vec<String>, // empty for any state
+    pub to: String,
+    pub when: Condition,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Machine {
+    pub initial: String,
+    pub states: HashMap<String, String>, // state name to clip name
+    pub transitions: Vec<Transition>,
+}
+
+impl Machine {
+    pub fn clip_name(&self, state: &str) -> &str {
+        &self.states[state]
+    }
+}
+
+// Loaded from resources/animations.ron
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AnimationData {
+    pub clips: HashMap<String, ClipData>,
+    pub machines: HashMap<String, Machine>,
+}
+
+pub struct Frame {
+    pub sprite: usize,
+    pub duration: f32,
+    pub events: Vec<String>,
+}
+
+pub struct Clip {
+    pub frames: Vec<Frame>,
+    pub looping: bool,
+}
+
+#[derive(Default)]
+pub struct Animations {
+    clips: HashMap<String, Clip>,
+    machines: HashMap<String, Machine>,
+}
+
+impl Animations {
+    // Resolves clip ranges to sprites and checks machines only refer
+    // to clips and states that exist, panics listing anything missing
+    pub fn load(names: &SpriteNames) -> Animations {
+        let path = format!("{}/resources/animations.ron", application_root_dir());
+        let data = AnimationData::load_no_fallback(&path)
+            .unwrap_or_else(|e| panic!("Failed to load animations {}: {}", path, e));
+
+        let mut resolver = SpriteResolver::new(names);
+        let mut clips = HashMap::new();
+        for (name, clip) in data.clips {
+            let context = format!("clip {}", name);
+            let range = resolver.animation(&context, &clip.range);
+            let frames = (0..range.count)
+                .map(|i| Frame {
+                    sprite: range.first + i,
+                    duration: *clip.durations.get(i).unwrap_or(&clip.frame_time),
+                    events: clip.events.iter()
+                        .filter(|event| event.frame == i)
+                        .map(|event| event.name.clone())
+                        .collect(),
+                })
+                .collect();
+            for event in clip.events.iter() {
+                if event.frame >= range.count {
+                    resolver.report(format!("{}: frame {} for event \"{}\"",
+                        context, event.frame, event.name));
+                }
+            }
+            clips.insert(name, Clip { frames, looping: clip.looping });
+        }
+
+        for (name, machine) in data.machines.iter() {
+            let context = format!("machine {}", name);
+            if !machine.states.contains_key(&machine.initial) {
+                resolver.report(format!("{}: initial state \"{}\"", context, machine.initial));
+            }
+            for clip in machine.states.values() {
+                if !clips.contains_key(clip) {
+                    resolver.report(format!("{}: clip \"{}\"", context, clip));
+                }
+            }
+            for transition in machine.transitions.iter() {
+                for state in transition.from.iter().chain(Some(&transition.to)) {
+                    if !machine.states.contains_key(state) {
+                        resolver.report(format!("{}: state \"{}\"", context, state));
+                    }
+                }
+            }
+        }
+        resolver.finish();
+
+        Animations {
+            clips,
+            machines: data.machines,
+        }
+    }
+
+    pub fn has_machine(&self, name: &str) -> bool {
+        self.machines.contains_key(name)
+    }
+
+    pub fn machine(&self, name: &str) -> &Machine {
+        &self.machines[name]
+    }
+
+    pub fn clip(&self, name: &str) -> &Clip {
+        &self.clips[name]
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use amethyst::config::Config;
+use amethyst::utils::application_root_dir;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LayerData {
+    pub texture: String,
+    pub width: u32, // pixel size of the texture
+    pub height: u32,
+    pub scroll: (f32, f32), // 0 is fixed to the screen, 1 to the world
+    pub offset: (f32, f32),
+    pub z: f32,
+    pub tile_x: bool,
+    pub tile_y: bool,
+}
+
+impl Default for LayerData {
+    fn default() -> LayerData {
+        LayerData {
+            texture: String::new(),
+            width: 1,
+            height: 1,
+            scroll: (1.0, 1.0),
+            offset: (0.0, 0.0),
+            z: -10.0,
+            tile_x: false,
+            tile_y: false,
+        }
+    }
+}
+
+// Loaded from resources/backgrounds.ron
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Backgrounds {
+    pub backgrounds: HashMap<String, Vec<LayerData>>,
+}
+
+impl Backgrounds {
+    pub fn load() -> Backgrounds {
+        let path = format!("{}/resources/backgrounds.ron", application_root_dir());
+        Backgrounds::load_no_fallback(&path)
+            .unwrap_or_else(|e| panic!("Failed to load backgrounds {}: {}", path, e))
+    }
+
+    // layers back to front
+    pub fn layers(&self, name: &str) -> Vec<LayerData> {
+        let mut layers = self.backgrounds.get(name)
+            .unwrap_or_else(|| panic!("Unknown background {}", name))
+            .clone();
+        layers.sort_by(|a, b| a.z.partial_cmp(&b.z).unwrap());
+        layers
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use amethyst::config::Config;
+use amethyst::utils::application_root_dir;
+use serde::{Deserialize, Serialize};
+
+use crate::generators::GeneratorKind;
+
+// A prefab filling one enemy role, weight scales how often the
+// difficulty profile places it
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EnemySlot {
+    pub prefab: String,
+    pub weight: f32,
+}
+
+// See resources/biomes.ron
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Biome {
+    pub generator: GeneratorKind,
+    pub background: String, // in resources/backgrounds.ron
+    pub platform: String, // prefab the level is built from
+    pub walker: EnemySlot,
+    pub ranged: EnemySlot,
+    pub flyer: EnemySlot,
+    #[serde(default)]
+    pub boss: Option<String>, // prefab guarding the gate
+    // what spawners make instead of the prefabs they ask for
+    #[serde(default)]
+    pub spawns: HashMap<String, String>,
+}
+
+impl Biome {
+    // the biome's own version of a prefab a spawner asked for
+    pub fn spawn<'a>(&'a self, prefab: &'a str) -> &'a str {
+        self.spawns.get(prefab).map_or(prefab, |swapped| swapped.as_str())
+    }
+
+    pub fn spawned_prefabs(&self) -> Vec<&String> {
+        vec![&self.platform, &self.walker.prefab, &self.ranged.prefab, &self.flyer.prefab]
+            .into_iter()
+            .chain(self.boss.iter())
+            .chain(self.spawns.values())
+            .collect()
+    }
+}
+
+// Loaded from resources/biomes.ron
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Biomes {
+    pub biomes: HashMap<String, Biome>,
+}
+
+impl Biomes {
+    pub fn load() -> Biomes {
+        let path = format!("{}/resources/biomes.ron", application_root_dir());
+        Biomes::load_no_fallback(&path)
+            .unwrap_or_else(|e| panic!("Failed to load biomes {}: {}", path, e))
+    }
+
+    pub fn get(&self, name: &str) -> Biome {
+        self.biomes.get(name)
+            .unwrap_or_else(|| panic!("Unknown biome {}", name))
+            .clone()
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use amethyst::config::Config;
+use amethyst::utils::application_root_dir;
+use serde::{Deserialize, Serialize};
+
+// One attack a boss can make, phases cycle through a list of these
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum BossPattern {
+    // run at the player along the platform after a pause
+    Charge { windup: f32, speed: f32, duration: f32 },
+    // fan of projectile prefabs aimed at the player, spread in degrees
+    Volley { prefab: String, count: usize, spread: f32, speed: f32 },
+    // call in helpers around the boss
+    Summon { prefab: String, count: usize },
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BossPhase {
+    pub below: f32, // starts once health drops to this fraction
+    pub walk_speed: f32,
+    pub cooldown: f32, // between attacks
+    pub patterns: Vec<BossPattern>,
+}
+
+// See resources/bosses.ron
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BossKind {
+    pub name: String, // shown over the health bar
+    pub arena: (f32, f32), // size of the box the player is kept in
+    pub phases: Vec<BossPhase>,
+}
+
+impl BossKind {
+    // the phase for what's left of the boss's health
+    pub fn phase_for(&self, fraction: f32) -> usize {
+        self.phases.iter().rposition(|phase| fraction <= phase.below).unwrap_or(0)
+    }
+
+    // prefabs the boss's attacks spawn
+    pub fn spawned_prefabs(&self) -> Vec<&String> {
+        self.phases.iter()
+            .flat_map(|phase| phase.patterns.iter())
+            .filter_map(|pattern| match pattern {
+                BossPattern::Volley { prefab, .. } | BossPattern::Summon { prefab, .. } => Some(prefab),
+                BossPattern::Charge { .. } => None,
+            })
+            .collect()
+    }
+}
+
+// Loaded from resources/bosses.ron
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Bosses {
+    pub bosses: HashMap<String, BossKind>,
+}
+
+impl Bosses {
+    pub fn load() -> Bosses {
+        let path = format!("{}/resources/bosses.ron", application_root_dir());
+        Bosses::load_no_fallback(&path)
+            .unwrap_or_else(|e| panic!("Failed to load bosses {}: {}", path, e))
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.bosses.contains_key(name)
+    }
+
+    pub fn get(&self, name: &str) -> &BossKind {
+        &self.bosses[name]
+    }
+}
@@ -23,4 +23,35 @@ pub fn check_collision(collider_1: &Collider, transform_1: &Transform,
     let wb = translation_2.y - collider_2.height / 2.0;
 
     return wl < pr && wr > pl && wt > pb && wb < pt;
-}
\ No newline at end of file
+}
+
+// whether the segment between two points passes through the collider,
+// clipped against each axis of the box in turn
+pub fn segment_hits(collider: &Collider, transform: &Transform,
+                    from: (f32, f32), to: (f32, f32)) -> bool {
+    let translation = transform.translation();
+    let min = (translation.x - collider.width / 2.0, translation.y - collider.height / 2.0);
+    let max = (translation.x + collider.width / 2.0, translation.y + collider.height / 2.0);
+
+    let mut enter: f32 = 0.0;
+    let mut exit: f32 = 1.0;
+    for &(start, delta, low, high) in [
+        (from.0, to.0 - from.0, min.0, max.0),
+        (from.1, to.1 - from.1, min.1, max.1),
+    ].iter() {
+        if delta.abs() < std::f32::EPSILON {
+            if start < low || start > high {
+                return false;
+            }
+            continue;
+        }
+        let t1 = (low - start) / delta;
+        let t2 = (high - start) / delta;
+        enter = enter.max(t1.min(t2));
+        exit = exit.min(t1.max(t2));
+        if enter > exit {
+            return false;
+        }
+    }
+    true
+}
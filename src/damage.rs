@@ -0,0 +1,37 @@
+use amethyst::ecs::Entity;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum DamageKind {
+    Contact,
+    Blast,
+    Acid,
+}
+
+// What a hit does, carried by whatever deals it
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Damage {
+    pub amount: i32,
+    pub kind: DamageKind,
+    pub knockback: f32, // speed the target is pushed away from the source
+}
+
+impl Default for Damage {
+    fn default() -> Damage {
+        Damage {
+            amount: 1,
+            kind: DamageKind::Contact,
+            knockback: 0.0,
+        }
+    }
+}
+
+// Published on an EventChannel for DamageSystem to apply
+#[derive(Debug, Clone)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub source_x: f32,
+    pub source_y: f32,
+    pub damage: Damage,
+}
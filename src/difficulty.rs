@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use amethyst::config::Config;
+use amethyst::utils::application_root_dir;
+use serde::{Deserialize, Serialize};
+
+// A value that changes from `start` where the player starts to `end`
+// at the gate
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+pub struct Curve {
+    pub start: f32,
+    pub end: f32,
+}
+
+impl Curve {
+    // progress from 0 at the start to 1 at the gate, see generators::Spot
+    pub fn at(&self, progress: f32) -> f32 {
+        let progress = progress.max(0.0).min(1.0);
+        self.start + (self.end - self.start) * progress
+    }
+}
+
+// How the level generator fills in a level, see resources/difficulty.ron
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DifficultyProfile {
+    // chance of each enemy type on a platform, one roll picks between
+    // them so together they should stay at or under 1
+    pub enemy_chance: Curve,
+    pub ranged_enemy_chance: Curve,
+    pub flying_enemy_chance: Curve,
+    pub spawner_chance: Curve,
+    pub wave_spawner_chance: Curve, // spawners in wave mode
+    // space between staggered platforms and how far they're jostled
+    pub gap: Curve,
+    pub wiggle_x: Curve,
+    pub wiggle_y: Curve,
+    // chance each ingredient that could grow on a platform does
+    pub hornwort_chance: Curve,
+    pub mushroom_chance: Curve,
+}
+
+// Loaded from resources/difficulty.ron
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Difficulty {
+    pub presets: HashMap<String, DifficultyProfile>,
+}
+
+impl Difficulty {
+    pub fn load() -> Difficulty {
+        let path = format!("{}/resources/difficulty.ron", application_root_dir());
+        Difficulty::load_no_fallback(&path)
+            .unwrap_or_else(|e| panic!("Failed to load difficulty {}: {}", path, e))
+    }
+
+    pub fn preset(&self, name: &str) -> DifficultyProfile {
+        self.presets.get(name)
+            .unwrap_or_else(|| panic!("Unknown difficulty {}", name))
+            .clone()
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use amethyst::config::Config;
+use amethyst::utils::application_root_dir;
+use serde::{Deserialize, Serialize};
+
+use crate::damage::{Damage, DamageKind};
+
+// Movement of enemies that ignore platforms
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Flight {
+    pub patrol_range: f32, // drifts this far either side of where it spawned
+    pub hover_height: f32, // above the player while chasing
+    pub bob_height: f32, // sine hover on top of everything else
+    pub bob_frequency: f32,
+}
+
+impl Default for Flight {
+    fn default() -> Flight {
+        Flight {
+            patrol_range: 60.0,
+            hover_height: 50.0,
+            bob_height: 8.0,
+            bob_frequency: 0.8,
+        }
+    }
+}
+
+// One line of an enemy's loot table, rolled separately on death
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LootDrop {
+    pub prefab: String,
+    pub chance: f32,
+    #[serde(default = "one")]
+    pub count: usize,
+}
+
+fn one() -> usize {
+    1
+}
+
+// How one type of enemy behaves, see resources/enemies.ron
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EnemyKind {
+    pub patrol_speed: f32,
+    pub chase_speed: f32,
+    // the player is noticed inside this box in front of the enemy
+    // when no platform blocks the line between them
+    pub sight_range: f32,
+    pub sight_height: f32,
+    pub notice_time: f32, // pause before giving chase
+    pub lose_time: f32, // out of sight this long and the chase is over
+    pub attack_range: f32,
+    pub windup_time: f32,
+    pub attack_time: f32,
+    pub attack_speed: f32, // lunge speed during the attack
+    pub cooldown_time: f32,
+    pub contact_damage: Damage, // dealt to a player touching the enemy
+    // prefab fired at the player when attacking, instead of lunging
+    pub projectile: Option<String>,
+    pub projectile_speed: f32,
+    // flyers swoop at attack_speed instead of lunging along a platform
+    pub flight: Option<Flight>,
+    // chase across platforms along the navigation graph instead of
+    // waiting at the edge of our own
+    pub pathfinding: bool,
+    pub loot: Vec<LootDrop>,
+}
+
+impl Default for EnemyKind {
+    fn default() -> EnemyKind {
+        EnemyKind {
+            patrol_speed: 50.0,
+            chase_speed: 90.0,
+            sight_range: 150.0,
+            sight_height: 40.0,
+            notice_time: 0.4,
+            lose_time: 1.5,
+            attack_range: 25.0,
+            windup_time: 0.3,
+            attack_time: 0.2,
+            attack_speed: 200.0,
+            cooldown_time: 0.8,
+            contact_damage: Damage {
+                amount: 40,
+                kind: DamageKind::Contact,
+                knockback: 100.0,
+            },
+            projectile: None,
+            projectile_speed: 150.0,
+            flight: None,
+            pathfinding: false,
+            loot: Vec::new(),
+        }
+    }
+}
+
+// Loaded from resources/enemies.ron
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct EnemyKinds {
+    pub enemies: HashMap<String, EnemyKind>,
+}
+
+impl EnemyKinds {
+    pub fn load() -> EnemyKinds {
+        let path = format!("{}/resources/enemies.ron", application_root_dir());
+        EnemyKinds::load_no_fallback(&path)
+            .unwrap_or_else(|e| panic!("Failed to load enemies {}: {}", path, e))
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.enemies.contains_key(name)
+    }
+
+    pub fn get(&self, name: &str) -> &EnemyKind {
+        &self.enemies[name]
+    }
+}
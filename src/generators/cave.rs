@@ -0,0 +1,55 @@
+use rand::Rng;
+
+use crate::difficulty::DifficultyProfile;
+use crate::navigation::landing_time;
+use crate::systems::mover::JUMP_VELOCITY;
+use crate::states::{Collider, LEVEL_HEIGHT, LEVEL_WIDTH};
+use super::{Layout, LevelGenerator, Spot};
+
+// how far each step may climb or fall, the climb well under the top
+// of a jump
+const MAX_CLIMB: f32 = 60.0;
+const MAX_FALL: f32 = 120.0;
+// the player's running speed in systems::player, and how much of a
+// running jump's reach to count on
+const RUN_SPEED: f32 = 140.0;
+const REACH_SHARE: f32 = 0.8;
+
+// the widest gap a running jump clears while climbing height, the
+// same arc navigation plans enemy jumps with
+fn reach(height: f32) -> f32 {
+    landing_time(JUMP_VELOCITY, height).map_or(0.0, |t| t * RUN_SPEED * REACH_SHARE)
+}
+
+// A random walk from the left of the level to the right, rising and
+// falling as it goes, with the gate at the far end
+pub struct CaveGenerator {
+}
+
+impl LevelGenerator for CaveGenerator {
+    fn generate(&self, profile: &DifficultyProfile, platform: &Collider) -> Layout {
+        let mut rng = rand::thread_rng();
+        let mut layout = Layout::default();
+        let (floor, ceiling) = (platform.height / 2.0, LEVEL_HEIGHT);
+
+        let mut x = platform.width / 2.0;
+        let mut y = LEVEL_HEIGHT / 2.0;
+        while x < LEVEL_WIDTH - platform.width / 2.0 {
+            let progress = x / LEVEL_WIDTH;
+            let spot = Spot { x, y, progress };
+            if let Some(previous) = layout.platforms.last().cloned() {
+                layout.add_gap(previous, spot);
+            }
+            layout.platforms.push(spot);
+
+            let next_y = (y + rng.gen_range(-MAX_FALL, MAX_CLIMB)).max(floor).min(ceiling);
+            let gap = profile.gap.at(progress) + profile.wiggle_x.at(progress) * rng.gen::<f32>() / 2.0;
+            x += platform.width + gap.min(reach(next_y - y));
+            y = next_y;
+        }
+
+        layout.start = 0;
+        layout.gate = layout.platforms.len() - 1;
+        layout
+    }
+}
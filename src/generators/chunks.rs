@@ -0,0 +1,73 @@
+use rand::Rng;
+
+use amethyst::config::Config;
+use amethyst::utils::application_root_dir;
+use serde::{Deserialize, Serialize};
+
+use crate::difficulty::DifficultyProfile;
+use crate::states::{Collider, LEVEL_HEIGHT, LEVEL_WIDTH};
+use super::{Layout, LevelGenerator, Spot};
+
+// height the first chunk starts at
+const START_HEIGHT: f32 = 150.0;
+
+// A hand-made piece of level. Platform centres are measured from the
+// chunk's left edge and the height it starts at, and the next chunk
+// starts `rise` higher up
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Chunk {
+    pub width: f32,
+    pub rise: f32,
+    pub platforms: Vec<(f32, f32)>,
+}
+
+// Chunks from resources/chunks.ron stitched left to right across the
+// level, picked at random from those that keep it in bounds
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ChunkGenerator {
+    pub chunks: Vec<Chunk>,
+}
+
+impl ChunkGenerator {
+    pub fn load() -> ChunkGenerator {
+        let path = format!("{}/resources/chunks.ron", application_root_dir());
+        ChunkGenerator::load_no_fallback(&path)
+            .unwrap_or_else(|e| panic!("Failed to load chunks {}: {}", path, e))
+    }
+}
+
+impl LevelGenerator for ChunkGenerator {
+    fn generate(&self, _profile: &DifficultyProfile, platform: &Collider) -> Layout {
+        let mut rng = rand::thread_rng();
+        let mut layout = Layout::default();
+
+        let (mut x, mut y) = (0.0, START_HEIGHT);
+        loop {
+            let fits: Vec<&Chunk> = self.chunks.iter()
+                .filter(|chunk| x + chunk.width <= LEVEL_WIDTH &&
+                    y + chunk.rise >= platform.height && y + chunk.rise <= LEVEL_HEIGHT)
+                .collect();
+            if fits.is_empty() {
+                break;
+            }
+            let chunk = fits[rng.gen_range(0, fits.len())];
+            for (px, py) in chunk.platforms.iter() {
+                // no difficulty curves to read, so just how far across we are
+                let spot = Spot { x: x + px, y: y + py, progress: (x + px) / LEVEL_WIDTH };
+                if let Some(previous) = layout.platforms.last().cloned() {
+                    layout.add_gap(previous, spot);
+                }
+                layout.platforms.push(spot);
+            }
+            x += chunk.width;
+            y += chunk.rise;
+        }
+
+        if layout.platforms.is_empty() {
+            panic!("No chunk in resources/chunks.ron fits the level");
+        }
+        layout.start = 0;
+        layout.gate = layout.platforms.len() - 1;
+        layout
+    }
+}
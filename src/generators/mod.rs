@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use crate::difficulty::DifficultyProfile;
+use crate::states::Collider;
+
+mod staggered;
+mod tower;
+mod cave;
+mod chunks;
+
+pub use self::staggered::StaggeredGenerator;
+pub use self::tower::TowerGenerator;
+pub use self::cave::CaveGenerator;
+pub use self::chunks::ChunkGenerator;
+
+// A point in the level and how far along the way to the gate it is,
+// from 0 at the start to 1. Generators read the difficulty curves at
+// the same progress they record here, so LevelState fills the level in
+// at the same pace it was laid out
+#[derive(Debug, Clone, Copy)]
+pub struct Spot {
+    pub x: f32,
+    pub y: f32,
+    pub progress: f32,
+}
+
+// how far over the higher of two platforms flyers wait between them
+const HOVER_HEIGHT: f32 = 50.0;
+
+// Where a generator put the platforms, LevelState fills them in with
+// enemies, ingredients and the gate
+#[derive(Debug, Default)]
+pub struct Layout {
+    pub platforms: Vec<Spot>, // centres
+    pub gaps: Vec<Spot>, // open air between platforms, where flyers wait
+    pub start: usize, // platform the player starts on
+    pub gate: usize, // platform the gate goes on
+}
+
+impl Layout {
+    // the air between two platforms the player gets across by jumping
+    pub fn add_gap(&mut self, a: Spot, b: Spot) {
+        self.gaps.push(Spot {
+            x: (a.x + b.x) / 2.0,
+            y: a.y.max(b.y) + HOVER_HEIGHT,
+            progress: (a.progress + b.progress) / 2.0,
+        });
+    }
+}
+
+// Lays out a level's platforms, each the size of the biome's platform
+// collider. The player has to be able to jump from the start to the
+// gate, see systems::mover for how far that is
+pub trait LevelGenerator {
+    fn generate(&self, profile: &DifficultyProfile, platform: &Collider) -> Layout;
+}
+
+// The generators a biome can pick from resources/biomes.ron
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum GeneratorKind {
+    Staggered, // rows of platforms climbing to the gate
+    Tower, // a narrow zig-zag climb
+    Cave, // a random walk from one side to the other
+    Chunks, // hand-made pieces from resources/chunks.ron
+}
+
+impl GeneratorKind {
+    pub fn generator(self) -> Box<dyn LevelGenerator> {
+        match self {
+            GeneratorKind::Staggered => Box::new(StaggeredGenerator {}),
+            GeneratorKind::Tower => Box::new(TowerGenerator {}),
+            GeneratorKind::Cave => Box::new(CaveGenerator {}),
+            GeneratorKind::Chunks => Box::new(ChunkGenerator::load()),
+        }
+    }
+}
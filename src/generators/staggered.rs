@@ -0,0 +1,60 @@
+use rand::Rng;
+
+use crate::difficulty::DifficultyProfile;
+use crate::states::{Collider, LEVEL_HEIGHT, LEVEL_WIDTH};
+use super::{Layout, LevelGenerator, Spot};
+
+// rows are this far apart, the gaps get wider further up
+const ROW_HEIGHT: f32 = 70.0;
+
+// Rows of platforms across the whole level, every other row shifted
+// along by a platform and a gap so you zig-zag up between them
+pub struct StaggeredGenerator {
+}
+
+impl LevelGenerator for StaggeredGenerator {
+    fn generate(&self, profile: &DifficultyProfile, platform: &Collider) -> Layout {
+        let mut rng = rand::thread_rng();
+        let mut layout = Layout::default();
+        let mut gate = None;
+        let mut offset = false;
+
+        let mut nat_y = platform.height / 2.0;
+        while nat_y < LEVEL_HEIGHT {
+            let progress = nat_y / LEVEL_HEIGHT;
+            let jump_x = platform.width + profile.gap.at(progress);
+            let wiggle_x = profile.wiggle_x.at(progress);
+            let wiggle_y = profile.wiggle_y.at(progress);
+
+            let mut nat_x = platform.width / 2.0;
+            if offset {
+                nat_x += jump_x;
+            }
+            offset = !offset;
+            let mut previous = None;
+            while nat_x < LEVEL_WIDTH - platform.width / 2.0 {
+                let x = nat_x + wiggle_x * rng.gen::<f32>();
+                let y = nat_y + wiggle_y * rng.gen::<f32>();
+
+                // gate somewhere in the top right, failing that the last platform
+                if gate.is_none() && x >= LEVEL_WIDTH / 2.0 && y >= LEVEL_HEIGHT / 2.0 &&
+                   rng.gen::<f32>() > 0.7 {
+                    gate = Some(layout.platforms.len());
+                }
+                let spot = Spot { x, y, progress };
+                // the empty slot between neighbours, under the next row
+                if let Some(previous) = previous {
+                    layout.add_gap(previous, spot);
+                }
+                previous = Some(spot);
+                layout.platforms.push(spot);
+                nat_x += jump_x * 2.0;
+            }
+            nat_y += ROW_HEIGHT;
+        }
+
+        layout.start = 0;
+        layout.gate = gate.unwrap_or(layout.platforms.len() - 1);
+        layout
+    }
+}
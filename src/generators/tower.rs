@@ -0,0 +1,55 @@
+use rand::Rng;
+
+use crate::difficulty::DifficultyProfile;
+use crate::states::{Collider, LEVEL_WIDTH};
+use super::{Layout, LevelGenerator, Spot};
+
+const TOWER_HEIGHT: f32 = 1400.0;
+const ROW_HEIGHT: f32 = 70.0;
+// chance of a dead-end ledge on the other side of a row
+const LEDGE_CHANCE: f32 = 0.3;
+
+// One platform per row swapping sides up a narrow shaft in the middle
+// of the level, with the odd ledge off to the side
+pub struct TowerGenerator {
+}
+
+impl LevelGenerator for TowerGenerator {
+    fn generate(&self, profile: &DifficultyProfile, platform: &Collider) -> Layout {
+        let mut rng = rand::thread_rng();
+        let mut layout = Layout::default();
+        let centre = LEVEL_WIDTH / 2.0;
+
+        let mut side = -1.0;
+        let mut previous = None;
+        let mut nat_y = platform.height / 2.0;
+        while nat_y < TOWER_HEIGHT {
+            let progress = nat_y / TOWER_HEIGHT;
+            let reach = (platform.width + profile.gap.at(progress)) / 2.0;
+            let wiggle_x = profile.wiggle_x.at(progress);
+            let y = nat_y + profile.wiggle_y.at(progress) * rng.gen::<f32>();
+
+            let x = centre + side * reach + wiggle_x * (rng.gen::<f32>() - 0.5);
+            let spot = Spot { x, y, progress };
+            // up the shaft between this row and the last
+            if let Some(previous) = previous {
+                layout.add_gap(previous, spot);
+            }
+            previous = Some(spot);
+            layout.platforms.push(spot);
+            // a ledge across the shaft, out past where the next row goes
+            if nat_y > ROW_HEIGHT && rng.gen::<f32>() < LEDGE_CHANCE {
+                layout.platforms.push(Spot { x: centre - side * (reach + platform.width), y, progress });
+            }
+            side = -side;
+            nat_y += ROW_HEIGHT;
+        }
+
+        layout.start = 0;
+        layout.gate = layout.platforms.iter().enumerate()
+            .max_by(|(_, a), (_, b)| a.y.partial_cmp(&b.y).unwrap())
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        layout
+    }
+}
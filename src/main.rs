@@ -10,6 +10,21 @@ use amethyst::audio::AudioBundle;
 mod states;
 mod systems;
 mod collision;
+mod recipes;
+mod spawn;
+mod prefabs;
+mod sprites;
+mod animations;
+mod particles;
+mod backgrounds;
+mod enemies;
+mod damage;
+mod bosses;
+mod navigation;
+mod waves;
+mod difficulty;
+mod generators;
+mod biomes;
 
 use states::{LevelState};
 
@@ -50,13 +65,32 @@ fn main() -> amethyst::Result<()> {
         .with_bundle(input_bundle)?
         .with_bundle(UiBundle::<String, String>::new())?
         .with_bundle(AudioBundle::new(|_: &mut NoMusic|{None}))?
+        .with(systems::BrewingSystem{}, "brewing_system", &["input_system"])
+        .with(systems::CursorSystem{}, "cursor_system", &["input_system"])
         .with(systems::PlayerSystem{health_tick_rate: 1.0, last_tick: 0.0}, 
-            "player_system",  &["input_system"])
+            "player_system",  &["brewing_system", "cursor_system"])
+        .with(systems::TrajectorySystem{}, "trajectory_system", &["player_system"])
         .with(systems::EnemySystem{}, "enemy_system", &["player_system"])
-        .with(systems::MoverSystem{}, "mover_system", &["enemy_system"])
-        .with(systems::SpriteAnimationSystem{}, "sprite_animation_system", &["mover_system"])
-        .with(systems::CameraSystem{}, "camera_system", &["player_system"])
-        .with(systems::PotionSystem{}, "potion_system", &[]);
+        .with(systems::FlyingSystem{}, "flying_system", &["player_system"])
+        .with(systems::BossSystem{}, "boss_system", &["player_system"])
+        .with(systems::WaveSystem{}, "wave_system", &[])
+        .with(systems::SpawnerSystem{}, "spawner_system", &["wave_system"])
+        .with(systems::MoverSystem{}, "mover_system", &["enemy_system", "boss_system"])
+        .with(systems::PotionSystem{}, "potion_system", &[])
+        .with(systems::ProjectileSystem{}, "projectile_system", &[])
+        .with(systems::DamageSystem{reader: None}, "damage_system",
+            &["enemy_system", "flying_system", "potion_system", "projectile_system"])
+        .with(systems::DamageFeedbackSystem{}, "damage_feedback_system", &["damage_system"])
+        .with(systems::SpriteAnimationSystem{}, "sprite_animation_system",
+            &["mover_system", "flying_system", "damage_feedback_system"])
+        .with(systems::AnimationSoundSystem{reader: None}, "animation_sound_system",
+            &["sprite_animation_system"])
+        .with(systems::AnimationParticleSystem{reader: None}, "animation_particle_system",
+            &["sprite_animation_system"])
+        .with(systems::ParticleSystem{}, "particle_system", &[])
+        .with(systems::CameraSystem::default(), "camera_system", &["mover_system"])
+        .with(systems::ParallaxSystem{}, "parallax_system", &["camera_system"])
+        .with(systems::HudLayoutSystem::default(), "hud_layout_system", &["camera_system"]);
 
     let mut game = Application::new("./", LevelState{sprite_sheet: None}, game_data)?;
 
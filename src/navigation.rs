@@ -0,0 +1,302 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use amethyst::{
+    core::Transform,
+    ecs::{Join, World},
+};
+
+use crate::states::{Collider, Platform};
+use crate::systems::mover::{GRAVITY, JUMP_VELOCITY};
+
+// platforms whose tops are this close line up well enough to walk across
+const STEP_HEIGHT: f32 = 4.0;
+const WALK_GAP: f32 = 2.0;
+// aim this far past the near edge of the platform we land on
+const LANDING_MARGIN: f32 = 10.0;
+// keep the top of the arc this far over the platform we jump onto
+const JUMP_CLEARANCE: f32 = 8.0;
+// feet can be this far above a platform and still count as on it
+const STANDING_HEIGHT: f32 = 40.0;
+
+// how a mover gets from one platform to another
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NavLink {
+    Walk,
+    Jump,
+    Drop, // walk off the edge and fall
+}
+
+#[derive(Debug, Clone)]
+pub struct NavEdge {
+    pub from: usize,
+    pub to: usize,
+    pub link: NavLink,
+    pub takeoff_x: f32, // where to leave the first platform
+    pub landing_x: f32, // where the arc comes down on the second
+    pub speed: f32, // horizontal speed that carries us from one to the other
+}
+
+#[derive(Debug, Clone)]
+pub struct NavNode {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+}
+
+// height above the takeoff point after t seconds in the air, the 1.2
+// matches the fudge in mover::integrate
+fn height_at(velocity_y: f32, t: f32) -> f32 {
+    1.2 * (velocity_y * t - GRAVITY * t * t / 2.0)
+}
+
+// when an arc starting upward at velocity_y comes down through height,
+// None if it never gets that high
+pub fn landing_time(velocity_y: f32, height: f32) -> Option<f32> {
+    let (a, b, c) = (0.6 * GRAVITY, -1.2 * velocity_y, height);
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    Some((-b + discriminant.sqrt()) / (2.0 * a))
+}
+
+// A* search state, ordered so the heap pops the cheapest estimate
+struct Open {
+    estimate: f32,
+    node: usize,
+}
+
+impl PartialEq for Open {
+    fn eq(&self, other: &Open) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl Eq for Open {}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Open) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Open {
+    fn cmp(&self, other: &Open) -> Ordering {
+        other.estimate.partial_cmp(&self.estimate).unwrap_or(Ordering::Equal)
+    }
+}
+
+// Platforms and the ways between them for a mover using default
+// gravity, built once the level is generated
+#[derive(Debug, Default)]
+pub struct NavGraph {
+    pub nodes: Vec<NavNode>,
+    pub edges: Vec<Vec<NavEdge>>, // leaving each node
+}
+
+impl NavGraph {
+    pub fn from_platforms(world: &World) -> NavGraph {
+        let platforms = world.read_storage::<Platform>();
+        let colliders = world.read_storage::<Collider>();
+        let transforms = world.read_storage::<Transform>();
+
+        let nodes: Vec<NavNode> = (&platforms, &colliders, &transforms).join()
+            .map(|(_platform, collider, transform)| {
+                let translation = transform.translation();
+                NavNode {
+                    left: translation.x - collider.width / 2.0,
+                    right: translation.x + collider.width / 2.0,
+                    top: translation.y + collider.height / 2.0,
+                }
+            })
+            .collect();
+        NavGraph::from_nodes(nodes)
+    }
+
+    pub fn from_nodes(nodes: Vec<NavNode>) -> NavGraph {
+        let edges = (0..nodes.len())
+            .map(|from| (0..nodes.len())
+                .filter(|to| *to != from)
+                .flat_map(|to| NavGraph::links(&nodes, from, to))
+                .collect())
+            .collect();
+        NavGraph { nodes, edges }
+    }
+
+    // every way off either end of one platform onto another
+    fn links(nodes: &[NavNode], from: usize, to: usize) -> Vec<NavEdge> {
+        let (a, b) = (&nodes[from], &nodes[to]);
+        let height = b.top - a.top;
+        let peak = height_at(JUMP_VELOCITY, JUMP_VELOCITY / GRAVITY);
+        let mut links = Vec::new();
+
+        for direction in [-1.0, 1.0].iter() {
+            let takeoff_x = if *direction > 0.0 { a.right } else { a.left };
+            // distance from the takeoff to the near and far edges of b
+            let (near, far) = if *direction > 0.0 {
+                (b.left - takeoff_x, b.right - takeoff_x)
+            } else {
+                (takeoff_x - b.right, takeoff_x - b.left)
+            };
+            if far <= LANDING_MARGIN {
+                continue;
+            }
+            let distance = near.max(0.0) + LANDING_MARGIN;
+            let landing_x = takeoff_x + direction * distance;
+            let edge = |link, speed| NavEdge { from, to, link, takeoff_x, landing_x, speed };
+
+            if height.abs() < STEP_HEIGHT && near < WALK_GAP {
+                links.push(edge(NavLink::Walk, 0.0));
+                continue;
+            }
+            if height < 0.0 {
+                if let Some(t) = landing_time(0.0, height) {
+                    links.push(edge(NavLink::Drop, distance / t));
+                }
+            }
+            // can't jump up through a platform overhead
+            if near > 0.0 && height < peak - JUMP_CLEARANCE {
+                if let Some(t) = landing_time(JUMP_VELOCITY, height) {
+                    links.push(edge(NavLink::Jump, distance / t));
+                }
+            }
+        }
+        links
+    }
+
+    // the platform a mover with its feet at (x, y) stands on
+    pub fn platform_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.nodes.iter().enumerate()
+            .filter(|(_, node)| x >= node.left && x <= node.right &&
+                y >= node.top - STEP_HEIGHT && y <= node.top + STANDING_HEIGHT)
+            .min_by(|(_, a), (_, b)| (y - a.top).partial_cmp(&(y - b.top)).unwrap_or(Ordering::Equal))
+            .map(|(i, _)| i)
+    }
+
+    // edges to follow from x on one platform to another, using only
+    // links a mover at max_speed can make; None when it can't get there
+    pub fn find_path(&self, from: usize, x: f32, to: usize, max_speed: f32) -> Option<Vec<NavEdge>> {
+        // distance to the nearest point on the goal platform, so it never
+        // overestimates and the first path to reach `to` is the cheapest
+        let goal = &self.nodes[to];
+        let heuristic = |node: usize, x: f32|
+            (x.max(goal.left).min(goal.right) - x).abs() + (goal.top - self.nodes[node].top).abs();
+
+        // cost, where we arrived and the edge that got us there
+        let mut best: HashMap<usize, (f32, f32, Option<NavEdge>)> = HashMap::new();
+        let mut open = BinaryHeap::new();
+        best.insert(from, (0.0, x, None));
+        open.push(Open { estimate: heuristic(from, x), node: from });
+
+        while let Some(Open { node, .. }) = open.pop() {
+            if node == to {
+                let mut path = Vec::new();
+                let mut current = to;
+                while let Some((_, _, Some(edge))) = best.get(&current) {
+                    current = edge.from;
+                    path.push(edge.clone());
+                }
+                path.reverse();
+                return Some(path);
+            }
+            let (cost, arrived_x, _) = best[&node];
+            for edge in self.edges[node].iter().filter(|edge| edge.speed <= max_speed) {
+                let next_cost = cost + (edge.takeoff_x - arrived_x).abs() +
+                    (edge.landing_x - edge.takeoff_x).abs() +
+                    (self.nodes[edge.to].top - self.nodes[node].top).abs();
+                if best.get(&edge.to).map_or(true, |(known, _, _)| next_cost < *known) {
+                    best.insert(edge.to, (next_cost, edge.landing_x, Some(edge.clone())));
+                    open.push(Open {
+                        estimate: next_cost + heuristic(edge.to, edge.landing_x),
+                        node: edge.to,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(left: f32, right: f32, top: f32) -> NavNode {
+        NavNode { left, right, top }
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.01
+    }
+
+    #[test]
+    fn landing_time_matches_the_arc() {
+        // straight back down to where it started
+        assert!(close(landing_time(JUMP_VELOCITY, 0.0).unwrap(), 2.0 * JUMP_VELOCITY / GRAVITY));
+        let t = landing_time(JUMP_VELOCITY, 30.0).unwrap();
+        assert!(close(height_at(JUMP_VELOCITY, t), 30.0));
+        let t = landing_time(0.0, -100.0).unwrap();
+        assert!(close(height_at(0.0, t), -100.0));
+        // higher than the top of the jump
+        assert!(landing_time(JUMP_VELOCITY, 100.0).is_none());
+    }
+
+    #[test]
+    fn level_neighbours_walk() {
+        let nodes = vec![node(0.0, 100.0, 0.0), node(101.0, 200.0, 0.0)];
+        let links = NavGraph::links(&nodes, 0, 1);
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].link, NavLink::Walk);
+        assert!(close(links[0].takeoff_x, 100.0));
+    }
+
+    #[test]
+    fn gap_up_is_a_jump() {
+        let nodes = vec![node(0.0, 100.0, 0.0), node(150.0, 250.0, 30.0)];
+        let links = NavGraph::links(&nodes, 0, 1);
+        assert_eq!(links.len(), 1);
+        let jump = &links[0];
+        assert_eq!(jump.link, NavLink::Jump);
+        assert!(close(jump.landing_x, 150.0 + LANDING_MARGIN));
+        let t = landing_time(JUMP_VELOCITY, 30.0).unwrap();
+        assert!(close(jump.speed, (jump.landing_x - jump.takeoff_x) / t));
+    }
+
+    #[test]
+    fn gap_down_can_drop_or_jump() {
+        let nodes = vec![node(0.0, 100.0, 100.0), node(150.0, 250.0, 0.0)];
+        let links = NavGraph::links(&nodes, 0, 1);
+        let kinds: Vec<NavLink> = links.iter().map(|edge| edge.link).collect();
+        assert_eq!(kinds, vec![NavLink::Drop, NavLink::Jump]);
+        // falling from rest takes less time, so needs more speed
+        assert!(links[0].speed > links[1].speed);
+    }
+
+    #[test]
+    fn path_walks_then_jumps() {
+        let graph = NavGraph::from_nodes(vec![
+            node(0.0, 100.0, 0.0),
+            node(101.0, 200.0, 0.0),
+            node(250.0, 350.0, 30.0),
+        ]);
+        let path = graph.find_path(0, 50.0, 2, 100.0).unwrap();
+        let steps: Vec<(usize, usize, NavLink)> = path.iter()
+            .map(|edge| (edge.from, edge.to, edge.link))
+            .collect();
+        assert_eq!(steps, vec![(0, 1, NavLink::Walk), (1, 2, NavLink::Jump)]);
+    }
+
+    #[test]
+    fn out_of_reach_has_no_path() {
+        let graph = NavGraph::from_nodes(vec![
+            node(0.0, 100.0, 0.0),
+            node(150.0, 250.0, 30.0),
+            node(300.0, 400.0, 200.0), // above the top of any jump
+        ]);
+        assert!(graph.find_path(0, 50.0, 2, 1000.0).is_none());
+        // the jump needs about 53 units/s across
+        assert!(graph.find_path(0, 50.0, 1, 40.0).is_none());
+        assert!(graph.find_path(0, 50.0, 1, 60.0).is_some());
+    }
+}
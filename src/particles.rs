@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use amethyst::config::Config;
+use amethyst::renderer::Rgba;
+use amethyst::utils::application_root_dir;
+use serde::{Deserialize, Serialize};
+
+use crate::sprites::{SpriteNames, SpriteResolver};
+use crate::states::ParticleEmitter;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EmitterData {
+    pub sprite: String,
+    pub tint: (f32, f32, f32, f32),
+    pub scale: f32,
+    pub burst: usize,
+    pub rate: f32,
+    pub duration: f32,
+    pub lifetime: f32,
+    pub speed: (f32, f32),
+    pub angle: (f32, f32),
+    pub gravity: f32,
+    pub spread_x: f32, // random horizontal offset from the emitter
+}
+
+impl Default for EmitterData {
+    fn default() -> EmitterData {
+        EmitterData {
+            sprite: String::new(),
+            tint: (1.0, 1.0, 1.0, 1.0),
+            scale: 1.0,
+            burst: 0,
+            rate: 0.0,
+            duration: 0.0,
+            lifetime: 1.0,
+            speed: (0.0, 0.0),
+            angle: (0.0, 360.0),
+            gravity: 0.0,
+            spread_x: 0.0,
+        }
+    }
+}
+
+// Loaded from resources/particles.ron
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ParticleData {
+    pub emitters: HashMap<String, EmitterData>,
+}
+
+#[derive(Default)]
+pub struct ParticleEffects {
+    emitters: HashMap<String, ParticleEmitter>,
+}
+
+impl ParticleEffects {
+    pub fn load(names: &SpriteNames) -> ParticleEffects {
+        let path = format!("{}/resources/particles.ron", application_root_dir());
+        let data = ParticleData::load_no_fallback(&path)
+            .unwrap_or_else(|e| panic!("Failed to load particles {}: {}", path, e));
+
+        let mut resolver = SpriteResolver::new(names);
+        let emitters = data.emitters.into_iter()
+            .map(|(name, emitter)| {
+                let sprite = resolver.sprite(&format!("emitter {}", name), &emitter.sprite);
+                let (r, g, b, a) = emitter.tint;
+                (name, ParticleEmitter {
+                    sprite,
+                    tint: Rgba(r, g, b, a),
+                    scale: emitter.scale,
+                    burst: emitter.burst,
+                    rate: emitter.rate,
+                    duration: emitter.duration,
+                    lifetime: emitter.lifetime,
+                    speed: emitter.speed,
+                    angle: emitter.angle,
+                    gravity: emitter.gravity,
+                    spread_x: emitter.spread_x,
+                    elapsed_time: 0.0,
+                    pending: 0.0,
+                    burst_done: false,
+                })
+            })
+            .collect();
+        resolver.finish();
+
+        ParticleEffects { emitters }
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.emitters.contains_key(name)
+    }
+
+    pub fn get(&self, name: &str) -> &ParticleEmitter {
+        &self.emitters[name]
+    }
+}
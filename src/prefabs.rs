@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use amethyst::config::Config;
+use amethyst::utils::application_root_dir;
+use serde::{Deserialize, Serialize};
+
+use crate::animations::Animations;
+use crate::enemies::EnemyKinds;
+use crate::bosses::Bosses;
+use crate::damage::DamageKind;
+use crate::particles::ParticleEffects;
+use crate::sprites::{SpriteNames, SpriteResolver};
+use crate::states::{Boss, Collider, Enemy, Health, Ingredient, ParticleEmitter, Potion, Projectile,
+    Spawner, SpriteAnimation};
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct MoverPrefab {
+    pub gravity: f32,
+    #[serde(default)]
+    pub friction: f32,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HealthPrefab {
+    pub max: i32,
+    pub hit_cooldown: f32,
+    pub resistances: HashMap<DamageKind, f32>,
+}
+
+// Components an entity is assembled from, one file per prefab in
+// resources/prefabs/, spawned by file name
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PrefabData {
+    pub sprite: String, // left out for entities that aren't drawn
+    pub z: f32,
+    pub scale: Option<f32>,
+    pub animation: Option<String>, // machine in resources/animations.ron
+    pub collider: Option<Collider>,
+    pub mover: Option<MoverPrefab>,
+    pub health: Option<HealthPrefab>,
+    pub potion: Option<Potion>,
+    pub projectile: Option<Projectile>,
+    pub ingredient: Option<Ingredient>,
+    pub player: bool,
+    pub enemy: Option<String>, // enemy type in resources/enemies.ron
+    pub flyer: bool,
+    pub boss: Option<String>, // boss in resources/bosses.ron
+    pub platform: bool,
+    pub gate: bool,
+    pub emitter: Option<String>, // particle effect in resources/particles.ron
+    pub spawner: Option<Spawner>,
+}
+
+// Prefab with its sprite names resolved to sheet indices
+#[derive(Debug, Clone)]
+pub struct Prefab {
+    pub sprite: Option<usize>,
+    pub z: f32,
+    pub scale: Option<f32>,
+    pub animation: Option<SpriteAnimation>,
+    pub collider: Option<Collider>,
+    pub mover: Option<MoverPrefab>,
+    pub health: Option<Health>,
+    pub potion: Option<Potion>,
+    pub projectile: Option<Projectile>,
+    pub ingredient: Option<Ingredient>,
+    pub player: bool,
+    pub enemy: Option<Enemy>,
+    pub flyer: bool,
+    pub boss: Option<Boss>,
+    pub platform: bool,
+    pub gate: bool,
+    pub emitter: Option<ParticleEmitter>,
+    pub spawner: Option<Spawner>,
+}
+
+impl Prefab {
+    fn resolve(name: &str, data: PrefabData, resolver: &mut SpriteResolver,
+               animations: &Animations, particles: &ParticleEffects,
+               enemies: &EnemyKinds, bosses: &Bosses) -> Prefab {
+        let context = format!("prefab {}", name);
+        let sprite = if data.sprite.is_empty() {
+            None
+        } else {
+            Some(resolver.sprite(&context, &data.sprite))
+        };
+        let animation = match data.animation {
+            Some(ref machine) if animations.has_machine(machine) => Some(
+                SpriteAnimation::new(machine, &animations.machine(machine).initial)),
+            Some(ref machine) => {
+                resolver.report(format!("{}: animation machine \"{}\"", context, machine));
+                None
+            },
+            None => None,
+        };
+        let emitter = match data.emitter {
+            Some(ref emitter) if particles.has(emitter) => Some(particles.get(emitter).clone()),
+            Some(ref emitter) => {
+                resolver.report(format!("{}: particle emitter \"{}\"", context, emitter));
+                None
+            },
+            None => None,
+        };
+        let enemy = match data.enemy {
+            Some(ref kind) if enemies.has(kind) => Some(Enemy::new(enemies.get(kind).clone())),
+            Some(ref kind) => {
+                resolver.report(format!("{}: enemy type \"{}\"", context, kind));
+                None
+            },
+            None => None,
+        };
+        let boss = match data.boss {
+            Some(ref kind) if bosses.has(kind) => Some(Boss::new(bosses.get(kind).clone())),
+            Some(ref kind) => {
+                resolver.report(format!("{}: boss \"{}\"", context, kind));
+                None
+            },
+            None => None,
+        };
+
+        Prefab {
+            sprite,
+            z: data.z,
+            scale: data.scale,
+            animation,
+            collider: data.collider,
+            mover: data.mover,
+            health: data.health.map(|health| Health {
+                resistances: health.resistances,
+                ..Health::new(health.max, health.hit_cooldown)
+            }),
+            potion: data.potion,
+            projectile: data.projectile,
+            ingredient: data.ingredient,
+            player: data.player,
+            enemy,
+            flyer: data.flyer,
+            boss,
+            platform: data.platform,
+            gate: data.gate,
+            emitter,
+            spawner: data.spawner,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Prefabs {
+    prefabs: HashMap<String, Prefab>,
+}
+
+impl Prefabs {
+    // Load every .ron file in resources/prefabs, panics on a bad file
+    // or unknown sprite name since the level can't be built without them
+    pub fn load(names: &SpriteNames, animations: &Animations,
+                particles: &ParticleEffects, enemies: &EnemyKinds,
+                bosses: &Bosses) -> Prefabs {
+        let directory = format!("{}/resources/prefabs", application_root_dir());
+        let entries = std::fs::read_dir(&directory)
+            .unwrap_or_else(|e| panic!("Failed to read prefab directory {}: {}", directory, e));
+
+        let mut resolver = SpriteResolver::new(names);
+        let mut prefabs = HashMap::new();
+        for entry in entries {
+            let path = entry.unwrap().path();
+            if path.extension().map_or(true, |extension| extension != "ron") {
+                continue;
+            }
+            let name = path.file_stem().unwrap().to_string_lossy().to_string();
+            let data = PrefabData::load_no_fallback(&path)
+                .unwrap_or_else(|e| panic!("Failed to load prefab {:?}: {}", path, e));
+            let prefab = Prefab::resolve(&name, data, &mut resolver, animations, particles,
+                enemies, bosses);
+            prefabs.insert(name, prefab);
+        }
+
+        // prefabs that spawn other prefabs
+        for (name, prefab) in prefabs.iter() {
+            let fired = prefab.enemy.as_ref().and_then(|enemy| enemy.kind.projectile.as_ref());
+            let splash = prefab.projectile.as_ref().and_then(|projectile| projectile.splash.as_ref());
+            let loot = prefab.enemy.iter().flat_map(|enemy| enemy.kind.loot.iter())
+                .map(|drop| &drop.prefab);
+            let attacks = prefab.boss.iter().flat_map(|boss| boss.kind.spawned_prefabs());
+            let spawns = prefab.spawner.iter().flat_map(|spawner| spawner.prefabs.iter());
+            for spawned in fired.into_iter().chain(splash).chain(loot).chain(attacks).chain(spawns) {
+                if !prefabs.contains_key(spawned) {
+                    resolver.report(format!("prefab {}: prefab \"{}\"", name, spawned));
+                }
+            }
+        }
+        resolver.finish();
+
+        Prefabs { prefabs }
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.prefabs.contains_key(name)
+    }
+
+    pub fn get(&self, name: &str) -> &Prefab {
+        self.prefabs.get(name)
+            .unwrap_or_else(|| panic!("No prefab named {}", name))
+    }
+}
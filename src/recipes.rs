@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use crate::damage::Damage;
+use crate::states::Player;
+
+// What happens when a recipe is brewed
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum RecipeEffect {
+    Throw(Damage),
+    Heal(i32),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Recipe {
+    pub name: String,
+    pub hornwort: usize,
+    pub mushroom: usize,
+    pub effect: RecipeEffect,
+}
+
+impl Recipe {
+    pub fn affordable(&self, player: &Player) -> bool {
+        player.hornwort >= self.hornwort && player.mushroom >= self.mushroom
+    }
+
+    pub fn consume(&self, player: &mut Player) {
+        player.hornwort -= self.hornwort;
+        player.mushroom -= self.mushroom;
+    }
+
+    pub fn label(&self, slot: usize) -> String {
+        format!("{} {} {}H {}M", slot + 1, self.name.to_uppercase(),
+            self.hornwort, self.mushroom)
+    }
+}
+
+// Loaded from resources/recipes.ron
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Recipes {
+    pub recipes: Vec<Recipe>,
+}
+
+impl Recipes {
+    // first recipe that heals, used for the quick heal button
+    pub fn heal_recipe(&self) -> Option<&Recipe> {
+        self.recipes.iter().find(|recipe| match recipe.effect {
+            RecipeEffect::Heal(_) => true,
+            _ => false,
+        })
+    }
+}
+
+// Currently selected recipe in the brewing hotbar
+#[derive(Default)]
+pub struct Brewing {
+    pub active: usize,
+}
@@ -0,0 +1,176 @@
+use amethyst::core::Transform;
+use amethyst::ecs::Entity;
+use amethyst::prelude::*;
+use amethyst::renderer::{SpriteRender, SpriteSheetHandle};
+
+use crate::damage::Damage;
+use crate::prefabs::Prefabs;
+use crate::states::{Flyer, Gate, Mover, Platform, Player, SpawnedBy};
+use crate::states::LEVEL_WIDTH;
+
+// A prefab to build at a position, see resources/prefabs
+#[derive(Debug, Clone)]
+pub struct SpawnRequest {
+    pub prefab: String,
+    pub x: f32,
+    pub y: f32,
+    pub velocity_x: f32,
+    pub velocity_y: f32,
+    pub bounds: Option<(f32, f32)>, // overrides the mover's min_x/max_x
+    pub damage: Option<Damage>, // overrides the potion's damage
+    pub owner: Option<Entity>, // spawner that asked for it
+}
+
+impl SpawnRequest {
+    pub fn at(prefab: &str, x: f32, y: f32) -> SpawnRequest {
+        SpawnRequest {
+            prefab: prefab.to_string(),
+            x,
+            y,
+            velocity_x: 0.0,
+            velocity_y: 0.0,
+            bounds: None,
+            damage: None,
+            owner: None,
+        }
+    }
+
+    pub fn with_velocity(mut self, velocity_x: f32, velocity_y: f32) -> SpawnRequest {
+        self.velocity_x = velocity_x;
+        self.velocity_y = velocity_y;
+        self
+    }
+
+    pub fn with_bounds(mut self, min_x: f32, max_x: f32) -> SpawnRequest {
+        self.bounds = Some((min_x, max_x));
+        self
+    }
+
+    pub fn with_damage(mut self, damage: Damage) -> SpawnRequest {
+        self.damage = Some(damage);
+        self
+    }
+
+    pub fn with_owner(mut self, owner: Entity) -> SpawnRequest {
+        self.owner = Some(owner);
+        self
+    }
+}
+
+// Systems can't reach the world or the sprite sheet so they push
+// requests here and the level state builds them once per frame
+#[derive(Default)]
+pub struct SpawnQueue {
+    requests: Vec<SpawnRequest>,
+}
+
+impl SpawnQueue {
+    pub fn push(&mut self, request: SpawnRequest) {
+        self.requests.push(request);
+    }
+
+    pub fn take(&mut self) -> Vec<SpawnRequest> {
+        std::mem::replace(&mut self.requests, Vec::new())
+    }
+}
+
+// how far movers may go when a request doesn't say
+pub fn default_bounds() -> (f32, f32) {
+    (-100.0, LEVEL_WIDTH + 100.0)
+}
+
+// for shots that should fly until ProjectileSystem removes them
+pub fn open_bounds() -> (f32, f32) {
+    (std::f32::MIN, std::f32::MAX)
+}
+
+// Drain every queued request into the world
+pub fn spawn_pending(world: &mut World, sprite_sheet: &SpriteSheetHandle) {
+    let requests = world.write_resource::<SpawnQueue>().take();
+    for request in requests {
+        spawn(world, sprite_sheet.clone(), &request);
+    }
+}
+
+pub fn spawn(world: &mut World, sprite_sheet: SpriteSheetHandle, request: &SpawnRequest) -> Entity {
+    let prefab = world.read_resource::<Prefabs>().get(&request.prefab).clone();
+
+    let mut transform = Transform::default();
+    transform.set_xyz(request.x, request.y, prefab.z);
+    if let Some(scale) = prefab.scale {
+        transform.set_scale(scale, scale, 1.0);
+    }
+
+    let mut builder = world
+        .create_entity()
+        .with(transform);
+
+    if let Some(sprite_number) = prefab.sprite {
+        builder = builder.with(SpriteRender {
+            sprite_sheet,
+            sprite_number,
+        });
+    }
+    if let Some(animation) = prefab.animation {
+        builder = builder.with(animation);
+    }
+    if let Some(collider) = prefab.collider {
+        builder = builder.with(collider);
+    }
+    if let Some(mover_prefab) = prefab.mover {
+        let (min_x, max_x) = request.bounds.unwrap_or_else(default_bounds);
+        let mut mover = Mover::new(min_x, max_x);
+        mover.velocity_x = request.velocity_x;
+        mover.velocity_y = request.velocity_y;
+        mover.gravity = mover_prefab.gravity;
+        mover.friction = mover_prefab.friction;
+        builder = builder.with(mover);
+    }
+    if let Some(health) = prefab.health {
+        builder = builder.with(health);
+    }
+    if let Some(mut potion) = prefab.potion {
+        if let Some(ref damage) = request.damage {
+            potion.damage = damage.clone();
+        }
+        builder = builder.with(potion);
+    }
+    if let Some(projectile) = prefab.projectile {
+        builder = builder.with(projectile);
+    }
+    if let Some(ingredient) = prefab.ingredient {
+        builder = builder.with(ingredient);
+    }
+    if prefab.player {
+        builder = builder.with(Player::new());
+    }
+    if let Some(enemy) = prefab.enemy {
+        builder = builder.with(enemy);
+    }
+    if prefab.flyer {
+        builder = builder.with(Flyer::new(request.x, request.y));
+    }
+    if let Some(mut boss) = prefab.boss {
+        boss.home_x = request.x;
+        boss.home_y = request.y;
+        builder = builder.with(boss);
+    }
+    if prefab.platform {
+        builder = builder.with(Platform{});
+    }
+    if prefab.gate {
+        builder = builder.with(Gate{locked: false});
+    }
+    if let Some(emitter) = prefab.emitter {
+        builder = builder.with(emitter);
+    }
+    if let Some(mut spawner) = prefab.spawner {
+        spawner.bounds = request.bounds;
+        builder = builder.with(spawner);
+    }
+    if let Some(spawner) = request.owner {
+        builder = builder.with(SpawnedBy{spawner});
+    }
+
+    builder.build()
+}
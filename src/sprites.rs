@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use amethyst::config::Config;
+use amethyst::utils::application_root_dir;
+use serde::{Deserialize, Serialize};
+
+// A run of consecutive frames in the sprite sheet
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+pub struct SpriteRange {
+    pub first: usize,
+    pub count: usize,
+}
+
+// Names for the frames of texture/spritesheet.ron, which itself
+// is just an anonymous list
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct SpriteNames {
+    sprites: HashMap<String, usize>,
+    animations: HashMap<String, SpriteRange>,
+}
+
+impl SpriteNames {
+    pub fn load() -> SpriteNames {
+        let path = format!("{}/texture/spritesheet_names.ron", application_root_dir());
+        SpriteNames::load_no_fallback(&path)
+            .unwrap_or_else(|e| panic!("Failed to load sprite names {}: {}", path, e))
+    }
+}
+
+// Resolves names while remembering every one that's missing so
+// startup can report them all at once instead of the first
+pub struct SpriteResolver<'a> {
+    names: &'a SpriteNames,
+    missing: Vec<String>,
+}
+
+impl<'a> SpriteResolver<'a> {
+    pub fn new(names: &'a SpriteNames) -> SpriteResolver<'a> {
+        SpriteResolver {
+            names,
+            missing: Vec::new(),
+        }
+    }
+
+    pub fn sprite(&mut self, context: &str, name: &str) -> usize {
+        match self.names.sprites.get(name) {
+            Some(sprite) => *sprite,
+            None => {
+                self.missing.push(format!("{}: sprite \"{}\"", context, name));
+                0
+            },
+        }
+    }
+
+    pub fn animation(&mut self, context: &str, name: &str) -> SpriteRange {
+        match self.names.animations.get(name) {
+            // clips index their frames, so an empty range can't play
+            Some(range) if range.count == 0 => {
+                self.missing.push(format!("{}: animation \"{}\" has no frames", context, name));
+                *range
+            },
+            Some(range) => *range,
+            None => {
+                self.missing.push(format!("{}: animation \"{}\"", context, name));
+                SpriteRange::default()
+            },
+        }
+    }
+
+    // something else referenced by name that doesn't exist
+    pub fn report(&mut self, missing: String) {
+        self.missing.push(missing);
+    }
+
+    // panic listing every missing name, if any
+    pub fn finish(self) {
+        if !self.missing.is_empty() {
+            panic!("Missing or empty names referenced by game data:\n{}",
+                self.missing.join("\n"));
+        }
+    }
+}
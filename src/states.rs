@@ -1,21 +1,43 @@
 extern crate rand;
 use rand::Rng;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 
 use amethyst::prelude::*;
 use amethyst::core::transform::Transform;
 use amethyst::core::nalgebra::Vector3;
 use amethyst::renderer::{
     Camera, Projection, PngFormat, SpriteSheetFormat, TextureMetadata, Texture,
-    SpriteSheet, SpriteSheetHandle, SpriteRender, Transparent, ScreenDimensions
+    SpriteSheet, SpriteSheetHandle, SpriteRender, Transparent, ScreenDimensions,
+    Hidden, Rgba, TextureData, Sprite,
 };
 use amethyst::assets::{AssetStorage, Loader};
-use amethyst::ecs::prelude::{Component, VecStorage, Entity};
+use amethyst::ecs::prelude::{Component, VecStorage, Entity, Join};
 use amethyst::ui::{
     Anchor, TtfFormat, UiText, UiTransform, UiImage,
 };
 use amethyst::input::is_key_down;
 use amethyst::Trans::*;
 use amethyst::audio::{output::Output, AudioSink, OggFormat, Source, SourceHandle};
+use amethyst::config::Config;
+use amethyst::utils::application_root_dir;
+
+use crate::recipes::{Brewing, Recipes};
+use crate::spawn::{spawn_pending, spawn, SpawnQueue, SpawnRequest};
+use crate::prefabs::Prefabs;
+use crate::sprites::{SpriteNames, SpriteResolver};
+use crate::animations::Animations;
+use crate::particles::ParticleEffects;
+use crate::backgrounds::Backgrounds;
+use crate::enemies::{EnemyKind, EnemyKinds};
+use crate::damage::{Damage, DamageKind};
+use crate::bosses::{BossKind, BossPattern, Bosses};
+use crate::navigation::{NavEdge, NavGraph};
+use crate::waves::Waves;
+use crate::difficulty::{Difficulty, DifficultyProfile};
+use crate::biomes::{Biome, Biomes};
+use crate::systems::camera::CameraConfig;
+use serde::{Deserialize, Serialize};
 
 pub const LEVEL_WIDTH: f32 = 3000.0;
 pub const LEVEL_HEIGHT: f32 = 600.0;
@@ -23,17 +45,54 @@ pub const LEVEL_HEIGHT: f32 = 600.0;
 pub const CAMERA_WIDTH: f32 = 400.0;
 pub const CAMERA_HEIGHT: f32 = 225.0;
 
+// window size the HUD layout was designed for
+pub const UI_WIDTH: f32 = 800.0;
+pub const UI_HEIGHT: f32 = 450.0;
+
 pub const PLATFORM_HEIGHT: f32 = 25.0;
-pub const PLATFORM_WIDTH: f32 = 100.0;
 pub const RESOURCE_WIDTH: f32 = 22.0;
 pub const RESOURCE_HEIGHT: f32 = 16.0;
 pub const PLAYER_HEIGHT: f32 = 25.0;
 pub const PLAYER_WIDTH: f32 = 10.0;
 pub const GATE_HEIGHT: f32 = 22.0;
 pub const GATE_WIDTH: f32 = 26.0;
+pub const BOSS_HEIGHT: f32 = 50.0;
+// flyers aren't placed this close to where the player starts
+pub const FLYER_START_CLEARANCE: f32 = 150.0;
 
 pub const POTION_SPEED: f32 = 200.0;
 
+pub const TRAJECTORY_DOTS: usize = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum GameMode {
+    Explore, // climb to the gate
+    Waves, // survive waves from spawners, see resources/waves.ron
+}
+
+// Loaded from resources/game.ron, what kind of run to start
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GameSettings {
+    pub mode: GameMode,
+    pub difficulty: String, // preset in resources/difficulty.ron
+    pub biome: String, // level layout, look and enemies from resources/biomes.ron
+}
+
+impl Default for GameSettings {
+    fn default() -> GameSettings {
+        GameSettings {
+            mode: GameMode::Explore,
+            difficulty: "normal".to_string(),
+            biome: "meadow".to_string(),
+        }
+    }
+}
+
+// boss health bar, in UI_WIDTH x UI_HEIGHT layout units
+pub const BOSS_BAR_WIDTH: f32 = 300.0;
+pub const BOSS_BAR_HEIGHT: f32 = 16.0;
+pub const BOSS_BAR_Y: f32 = -80.0;
+
 // UI:
 pub struct UiEntities {
     pub score_entity: Entity,
@@ -41,6 +100,110 @@ pub struct UiEntities {
     pub mushroom_entity: Entity,
     pub health_entity: Entity,
     pub game_over_entity: Entity,
+    pub recipe_entities: Vec<Entity>,
+    pub boss_name_entity: Entity,
+    pub boss_bar_entity: Entity,
+    pub boss_fill_entity: Entity,
+    pub wave_entity: Entity,
+}
+
+// Extents of the generated level, measured from its platforms
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LevelBounds {
+    pub min_x: f32,
+    pub max_x: f32,
+    pub min_y: f32,
+    pub max_y: f32,
+}
+
+impl LevelBounds {
+    fn from_platforms(world: &World) -> LevelBounds {
+        let platforms = world.read_storage::<Platform>();
+        let colliders = world.read_storage::<Collider>();
+        let transforms = world.read_storage::<Transform>();
+
+        let mut bounds: Option<LevelBounds> = None;
+        for (_platform, collider, transform) in (&platforms, &colliders, &transforms).join() {
+            let translation = transform.translation();
+            let left = translation.x - collider.width / 2.0;
+            let right = translation.x + collider.width / 2.0;
+            let bottom = translation.y - collider.height / 2.0;
+            let top = translation.y + collider.height / 2.0;
+            bounds = Some(match bounds {
+                Some(b) => LevelBounds {
+                    min_x: b.min_x.min(left),
+                    max_x: b.max_x.max(right),
+                    min_y: b.min_y.min(bottom),
+                    max_y: b.max_y.max(top),
+                },
+                None => LevelBounds { min_x: left, max_x: right, min_y: bottom, max_y: top },
+            });
+        }
+        bounds.unwrap_or_default()
+    }
+}
+
+// Requests from gameplay for the camera to react, CameraSystem
+// blends these on top of following the player
+#[derive(Default)]
+pub struct CameraEffects {
+    pub trauma: f32, // 0 to 1, shake grows with its square
+    pub zoom: f32, // fraction the view shrinks by
+    pub hit_stop: f32, // real seconds left of slow motion
+}
+
+impl CameraEffects {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+
+    pub fn zoom_punch(&mut self, amount: f32) {
+        self.zoom = self.zoom.max(amount);
+    }
+
+    pub fn hit_stop(&mut self, seconds: f32) {
+        self.hit_stop = self.hit_stop.max(seconds);
+    }
+}
+
+// World area the camera shows for the current window, see systems::camera
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ViewSize {
+    pub width: f32, // across the whole window
+    pub height: f32,
+    pub visible_width: f32, // not hidden by letterbox bars
+    pub visible_height: f32,
+    pub scale: f32, // screen pixels per world unit
+}
+
+// The boss fight in progress, if any
+#[derive(Default)]
+pub struct BossEncounter {
+    pub active: bool,
+    pub min_x: f32, // player is kept between these
+    pub max_x: f32,
+    pub barriers: Vec<Entity>, // particle curtains marking the arena edges
+}
+
+// Progress through resources/waves.ron in wave mode
+#[derive(Default)]
+pub struct WaveState {
+    pub wave: usize, // running or next, from 0
+    pub fighting: bool,
+    pub break_time: f32, // seconds into the current break
+    pub restocked: bool, // supplies dropped for the current break
+}
+
+// Mouse position in world coordinates, see systems::cursor
+#[derive(Default)]
+pub struct Cursor {
+    pub x: f32,
+    pub y: f32,
+}
+
+// Pool of dots used to preview the potion arc while aiming
+pub struct TrajectoryDots {
+    pub dots: Vec<Entity>,
 }
 
 #[derive(Default)]
@@ -59,27 +222,6 @@ pub struct SoundEffects {
     pub pickup: SourceHandle,
 }
 
-// implement a lazy spawner for simplicity..
-#[derive(Default, Debug)]
-pub struct PotionInfo {
-    pub px: f32,
-    pub py: f32,
-    pub mx: f32,
-    pub my: f32,
-}
-
-pub struct PotionSpawner {
-    pub potion: Option<PotionInfo>,
-}
-
-impl Default for PotionSpawner {
-    fn default() -> PotionSpawner {
-        PotionSpawner {
-            potion: std::option::Option::None,
-        }
-    }
-}
-
 #[derive(Default)]
 pub struct LevelState {
     pub sprite_sheet: Option<SpriteSheetHandle>,
@@ -88,197 +230,172 @@ pub struct LevelState {
 impl LevelState {
     fn create_entities(world: &mut World, sprite_sheet: SpriteSheetHandle){
         // create platform entities
-        let (plat_x, plat_y) = LevelState::generate_platforms(world, sprite_sheet.clone());
+        let (plat_x, plat_top) = LevelState::generate_level(world, sprite_sheet.clone());
 
         // Create the player entity:
-        let mut player_transform = Transform::default();
-        player_transform.set_xyz(plat_x, plat_y + PLATFORM_HEIGHT/2.0 + PLAYER_HEIGHT, 0.0);
-        let player_sprite_render = SpriteRender{
-            sprite_sheet: sprite_sheet.clone(),
-            sprite_number: 0,
-        };
-        let player_animation = SpriteAnimation::new(1, 6, 0, 1, 0.1, 7);
-
-        world
-            .create_entity()
-            .with(player_transform)
-            .with(player_sprite_render)
-            .with(player_animation)
-            .with(Player::new())
-            .with(Mover::new(-100.0, LEVEL_WIDTH + 100.0))
-            .with(Collider{width: 24.0, height: 25.0})
-            .build();
+        spawn(world, sprite_sheet,
+            &SpawnRequest::at("player", plat_x, plat_top + PLAYER_HEIGHT));
     }
 
-    fn generate_platforms(world: &mut World, sprite_sheet: SpriteSheetHandle) -> (f32, f32) {
+    // lays out and fills in the level, returns the top middle of the
+    // platform the player starts on
+    fn generate_level(world: &mut World, sprite_sheet: SpriteSheetHandle) -> (f32, f32) {
         let mut rng = rand::thread_rng();
+        let profile = world.read_resource::<DifficultyProfile>().clone();
+        let biome = world.read_resource::<Biome>().clone();
+        // checked for a collider in initialize_biome
+        let platform = world.read_resource::<Prefabs>().get(&biome.platform).collider.clone().unwrap();
+        let layout = biome.generator.generator().generate(&profile, &platform);
+        let waves = world.read_resource::<GameSettings>().mode == GameMode::Waves;
+
+        let (gate_x, gate_y) = (layout.platforms[layout.gate].x, layout.platforms[layout.gate].y);
+
+        // everything gets harder at the pace the generator laid it out
+        let (start_x, start_y) = (layout.platforms[layout.start].x, layout.platforms[layout.start].y);
+        let mut gap_taken = vec![false; layout.gaps.len()];
+        let mut spawner_spawned = false;
+        for (i, spot) in layout.platforms.iter().enumerate() {
+            let (x, y, progress) = (spot.x, spot.y, spot.progress);
+            let start = i == layout.start;
+
+            spawn(world, sprite_sheet.clone(), &SpawnRequest::at(&biome.platform, x, y));
+
+            // generate resources on this platform:
+            let min_x = x - platform.width / 2.0 + RESOURCE_WIDTH / 2.0;
+            let max_x = x + platform.width / 2.0 - RESOURCE_WIDTH / 2.0;
+
+            LevelState::generate_resources(world, sprite_sheet.clone(), 
+                                        y + platform.height / 2.0 + RESOURCE_HEIGHT / 2.0, min_x, max_x,
+                                        profile.hornwort_chance.at(progress),
+                                        profile.mushroom_chance.at(progress));
+
+            let roll: f32 = rng.gen();
+            let spawner_chance = if waves {
+                profile.wave_spawner_chance.at(progress)
+            } else {
+                profile.spawner_chance.at(progress)
+            };
+            if !start && roll < spawner_chance {
+                spawner_spawned = true;
+                spawn(world, sprite_sheet.clone(),
+                    &SpawnRequest::at("spawner", x, y + platform.height / 2.0 + 25.0)
+                        .with_bounds(min_x, max_x));
+            }
 
-        let jump_x =  PLATFORM_WIDTH + 50.0;
-        let jump_y = 70.0;
-        let wiggle_y = 30.0;
-        let wiggle_x = 35.0;
-        let mut offset = false;
-
-        let mut gate_spawned = false;
-        let mut gate_x = 0.0;
-        let mut gate_y = 0.0;
-
-        let mut first = true;
-
-        let mut nat_y = PLATFORM_HEIGHT / 2.0;
-        let mut ret_x = 0.0;
-        let mut ret_y = 0.0;
-        while nat_y < LEVEL_HEIGHT {
-            let mut nat_x = PLATFORM_WIDTH / 2.0;
-            if offset {
-                nat_x += jump_x;
-            } 
-            offset = !offset;
-            while nat_x < LEVEL_WIDTH - PLATFORM_WIDTH / 2.0 {
-                let rand_x: f32 = rng.gen();
-                let rand_y: f32 = rng.gen();
-                let x = nat_x + wiggle_x * rand_x;
-                let y = nat_y + wiggle_y * rand_y;
-                gate_y = y;
-                gate_x = x;
-
-                let mut platform_transform = Transform::default();
-                platform_transform.set_xyz(x, y, 0.0);
-                let platform_sprite_render = SpriteRender{
-                    sprite_sheet: sprite_sheet.clone(),
-                    sprite_number: 10,
-                };
-                world
-                    .create_entity()
-                    .with(platform_sprite_render)
-                    .with(platform_transform)
-                    .with(Platform{})
-                    .with(Collider{width: PLATFORM_WIDTH, height: PLATFORM_HEIGHT})
-                    .build();
-
-                // generate resources on this platform:
-                let min_x = x - PLATFORM_WIDTH / 2.0 + RESOURCE_WIDTH / 2.0;
-                let max_x = x + PLATFORM_WIDTH / 2.0 - RESOURCE_WIDTH / 2.0;
-
-                LevelState::generate_resources(world, sprite_sheet.clone(), 
-                                            y + PLATFORM_HEIGHT / 2.0 + RESOURCE_HEIGHT / 2.0, min_x, max_x);
-
-                // spawn gate
-                if !gate_spawned && (x >= LEVEL_WIDTH / 2.0 && y >= LEVEL_HEIGHT / 2.0) {
-                    let roll: f32 = rng.gen();
-                    if roll > 0.7 {
-                        gate_spawned = true;
-                        LevelState::spawn_gate(world, sprite_sheet.clone(),
-                            gate_x, gate_y);
-                    }
+            // one roll per platform picks at most one enemy, each type
+            // takes the band past the one before so the odds don't stack
+            let roll: f32 = rng.gen();
+            let walker_chance = profile.enemy_chance.at(progress) * biome.walker.weight;
+            let ranged_chance = walker_chance +
+                profile.ranged_enemy_chance.at(progress) * biome.ranged.weight;
+            let flying_chance = ranged_chance +
+                profile.flying_enemy_chance.at(progress) * biome.flyer.weight;
+            // survival levels only have what the spawners make
+            if waves || start {
+                continue;
+            }
+            if roll < ranged_chance {
+                let enemy = if roll < walker_chance { &biome.walker.prefab } else { &biome.ranged.prefab };
+                let mut velocity_x = super::systems::enemy::ENEMY_VELOCITY;
+                if rng.gen() {
+                    velocity_x = velocity_x * -1.0;
                 }
-                
-                // spawn enemy
-                if !first && rng.gen() {
-                    let mut enemy_transform = Transform::default();
-                    let mut enemy_mover = Mover::new(min_x, max_x);
-                    enemy_mover.velocity_x = super::systems::enemy::ENEMY_VELOCITY;
-                    if rng.gen() {
-                        enemy_mover.velocity_x = enemy_mover.velocity_x * -1.0;
+                spawn(world, sprite_sheet.clone(),
+                    &SpawnRequest::at(enemy, x, y + 25.0)
+                        .with_velocity(velocity_x, 0.0)
+                        .with_bounds(min_x, max_x));
+            } else if roll < flying_chance {
+                // flyers wait in the nearest free gap, clear of the start
+                let distance = |g: usize| (layout.gaps[g].x - x).powi(2) + (layout.gaps[g].y - y).powi(2);
+                let nearest = (0..layout.gaps.len())
+                    .filter(|&g| !gap_taken[g])
+                    .min_by(|&a, &b| distance(a).partial_cmp(&distance(b)).unwrap_or(Ordering::Equal));
+                if let Some(g) = nearest {
+                    let gap = layout.gaps[g];
+                    let near_start = (gap.x - start_x).abs() < FLYER_START_CLEARANCE &&
+                        (gap.y - start_y).abs() < FLYER_START_CLEARANCE;
+                    if !near_start {
+                        gap_taken[g] = true;
+                        spawn(world, sprite_sheet.clone(), &SpawnRequest::at(&biome.flyer.prefab, gap.x, gap.y));
                     }
-                    enemy_transform.set_xyz(x, y + 25.0, 0.0);
-                    world
-                        .create_entity()
-                        .with(enemy_transform)
-                        .with(Enemy{})
-                        .with(SpriteRender {
-                            sprite_sheet: sprite_sheet.clone(),
-                            sprite_number: 13,
-                        })
-                        .with(SpriteAnimation::new(14, 6, 13, 1, 0.1, 13))
-                        .with(enemy_mover)
-                        .with(Collider{width: PLAYER_WIDTH, height: PLAYER_HEIGHT})
-                        .build();
-                }
-
-                if first {
-                    first = false;
-                    ret_y = y;
-                    ret_x = x;
                 }
-
-
-                nat_x += jump_x * 2.0;
             }
-            nat_y += jump_y;
         }
 
-        if !gate_spawned {
-            LevelState::spawn_gate(world, sprite_sheet.clone(),
-            gate_x, gate_y);
+        if !waves {
+            LevelState::spawn_gate(world, sprite_sheet.clone(), gate_x, gate_y, &biome, &platform);
+        } else if !spawner_spawned {
+            // the waves need somewhere to come from
+            spawn(world, sprite_sheet.clone(),
+                &SpawnRequest::at("spawner", gate_x, gate_y + platform.height / 2.0 + 25.0)
+                    .with_bounds(gate_x - platform.width / 2.0, gate_x + platform.width / 2.0));
         }
-                
-        (ret_x, ret_y)
+
+        (start_x, start_y + platform.height / 2.0)
     }
 
     fn generate_resources(world: &mut World, sprite_sheet: SpriteSheetHandle,
-                          y: f32, x_min: f32, x_max: f32) {
+                          y: f32, x_min: f32, x_max: f32,
+                          hornwort_chance: f32, mushroom_chance: f32) {
         let mut rng = rand::thread_rng();
         let hornwort_count = rng.gen_range(0, 3);
         for _ in 0..hornwort_count {
-            if rng.gen() {
+            if rng.gen::<f32>() >= hornwort_chance {
                 continue;
             }
-            let mut transform = Transform::default();
             let x: f32 = rng.gen_range(x_min, x_max);
-            transform.set_xyz(x, y, -1.0); // TODO make sure Z value is read
-            world
-                .create_entity()
-                .with(transform)
-                .with(Collider{width: RESOURCE_WIDTH, height: RESOURCE_HEIGHT})
-                .with(SpriteRender {
-                    sprite_sheet: sprite_sheet.clone(),
-                    sprite_number: 12,
-                })
-                .with(Ingredient::Hornwort{count: 1})
-                .build();
+            spawn(world, sprite_sheet.clone(), &SpawnRequest::at("hornwort", x, y));
         }
 
         let mushroom_count = rng.gen_range(0, 2);
         for _ in 0..mushroom_count {
-            let mut transform = Transform::default();
+            if rng.gen::<f32>() >= mushroom_chance {
+                continue;
+            }
             let x: f32 = rng.gen_range(x_min, x_max);
-            transform.set_xyz(x, y, -1.0); 
-            world
-                .create_entity()
-                .with(transform)
-                .with(Collider{width: RESOURCE_WIDTH, height: RESOURCE_HEIGHT})
-                .with(SpriteRender {
-                    sprite_sheet: sprite_sheet.clone(),
-                    sprite_number: 11,
-                })
-                .with(Ingredient::Mushroom{count: 1})
-                .build();
+            spawn(world, sprite_sheet.clone(), &SpawnRequest::at("mushroom", x, y));
         }
 
     }
 
     fn spawn_gate(world: &mut World, sprite_sheet: SpriteSheetHandle,
-        px: f32, py: f32) {
-            let mut gate_transform = Transform::default();
-            gate_transform.set_xyz(px, py + PLATFORM_HEIGHT / 2.0 + GATE_HEIGHT / 2.0, 0.0);
-
-            world.
-                create_entity()
-                .with(gate_transform)
-                .with(Gate{})
-                .with(Collider{
-                    width: GATE_WIDTH,
-                    height: GATE_HEIGHT,
-                })
-                .with(SpriteRender {
-                    sprite_sheet: sprite_sheet,
-                    sprite_number: 9,
-                })
-                .build();
-
+        px: f32, py: f32, biome: &Biome, platform: &Collider) {
+            spawn(world, sprite_sheet.clone(),
+                &SpawnRequest::at("gate", px, py + platform.height / 2.0 + GATE_HEIGHT / 2.0));
+
+            // the boss paces the gate's platform
+            if let Some(ref boss) = biome.boss {
+                spawn(world, sprite_sheet,
+                    &SpawnRequest::at(boss, px, py + platform.height / 2.0 + BOSS_HEIGHT / 2.0)
+                        .with_bounds(px - platform.width / 2.0, px + platform.width / 2.0));
+            }
         }
     
+    fn initialize_difficulty(world: &mut World) {
+        let profile = Difficulty::load().preset(&world.read_resource::<GameSettings>().difficulty);
+        world.add_resource(profile);
+    }
+
+    fn initialize_biome(world: &mut World) {
+        let name = world.read_resource::<GameSettings>().biome.clone();
+        let biome = Biomes::load().get(&name);
+        {
+            let prefabs = world.read_resource::<Prefabs>();
+            let names = world.read_resource::<SpriteNames>();
+            let mut resolver = SpriteResolver::new(&names);
+            for prefab in biome.spawned_prefabs() {
+                if !prefabs.has(prefab) {
+                    resolver.report(format!("biome {}: prefab \"{}\"", name, prefab));
+                }
+            }
+            if prefabs.has(&biome.platform) && prefabs.get(&biome.platform).collider.is_none() {
+                resolver.report(format!("biome {}: collider on platform \"{}\"", name, biome.platform));
+            }
+            resolver.finish();
+        }
+        world.add_resource(biome);
+    }
+
     fn initialize_sound(world: &mut World) {
         let effects = {
             let loader = world.read_resource::<Loader>();
@@ -294,6 +411,46 @@ impl LevelState {
         world.add_resource(effects);
     }
 
+    fn initialize_recipes(world: &mut World) {
+        let recipes_path =
+            format!("{}/resources/recipes.ron", application_root_dir());
+        world.add_resource(Recipes::load(&recipes_path));
+        world.add_resource(Brewing::default());
+    }
+
+    fn initialize_settings(world: &mut World) {
+        let settings_path =
+            format!("{}/resources/game.ron", application_root_dir());
+        world.add_resource(GameSettings::load(&settings_path));
+    }
+
+    // names are checked here so a typo fails at startup, not mid game
+    fn initialize_prefabs(world: &mut World) {
+        let names = SpriteNames::load();
+        let animations = Animations::load(&names);
+        let particles = ParticleEffects::load(&names);
+        let enemies = EnemyKinds::load();
+        let bosses = Bosses::load();
+        let prefabs = Prefabs::load(&names, &animations, &particles, &enemies, &bosses);
+        if world.read_resource::<GameSettings>().mode == GameMode::Waves {
+            let waves = Waves::load();
+            let mut resolver = SpriteResolver::new(&names);
+            for prefab in waves.spawned_prefabs() {
+                if !prefabs.has(prefab) {
+                    resolver.report(format!("waves: prefab \"{}\"", prefab));
+                }
+            }
+            resolver.finish();
+            world.add_resource(waves);
+        }
+        world.add_resource(names);
+        world.add_resource(animations);
+        world.add_resource(particles);
+        world.add_resource(enemies);
+        world.add_resource(bosses);
+        world.add_resource(prefabs);
+    }
+
     fn initialize_ui(world: &mut World) {
         let font = world.read_resource::<Loader>().load(
             "font/square.ttf",
@@ -436,6 +593,98 @@ impl LevelState {
                 100.0,
             ))
             .build();
+
+        // wave counter, only used in wave mode, centred above the boss
+        // bar and clear of the ingredient counters
+        let wave_transform = UiTransform::new(
+            "wave".to_string(), Anchor::TopMiddle,
+            0.0, -20.0, 1.0, 240.0, 30.0, 0
+        );
+        let wave_entity = world
+            .create_entity()
+            .with(wave_transform)
+            .with(UiText::new(
+                font.clone(),
+                "".to_string(),
+                [0.0, 0.0, 0.0, 1.0],
+                25.0,
+            ))
+            .build();
+
+        // boss health bar, hidden until a fight starts
+        let (bar_texture, fill_texture) = {
+            let loader = world.read_resource::<Loader>();
+            let texture_storage =
+                world.read_resource::<AssetStorage<Texture>>();
+            (
+                loader.load_from_data(TextureData::from([0.1, 0.1, 0.1, 0.8]), (), &texture_storage),
+                loader.load_from_data(TextureData::from([0.8, 0.0, 0.0, 1.0]), (), &texture_storage),
+            )
+        };
+
+        let boss_name_transform = UiTransform::new(
+            "boss_name".to_string(), Anchor::TopMiddle,
+            0.0, BOSS_BAR_Y + 30.0, 1.0, BOSS_BAR_WIDTH, 30.0, 0
+        );
+        let boss_name_entity = world
+            .create_entity()
+            .with(boss_name_transform)
+            .with(UiText::new(
+                font.clone(),
+                "".to_string(),
+                [0.8, 0.0, 0.0, 1.0],
+                25.0,
+            ))
+            .with(Hidden)
+            .build();
+
+        let boss_bar_transform = UiTransform::new(
+            "boss_bar".to_string(), Anchor::TopMiddle,
+            0.0, BOSS_BAR_Y, 1.0, BOSS_BAR_WIDTH + 6.0, BOSS_BAR_HEIGHT + 6.0, 0
+        );
+        let boss_bar_entity = world
+            .create_entity()
+            .with(boss_bar_transform)
+            .with(UiImage { texture: bar_texture })
+            .with(Hidden)
+            .build();
+
+        // above the backing so it draws on top
+        let boss_fill_transform = UiTransform::new(
+            "boss_fill".to_string(), Anchor::TopMiddle,
+            0.0, BOSS_BAR_Y, 1.1, BOSS_BAR_WIDTH, BOSS_BAR_HEIGHT, 0
+        );
+        let boss_fill_entity = world
+            .create_entity()
+            .with(boss_fill_transform)
+            .with(UiImage { texture: fill_texture })
+            .with(Hidden)
+            .build();
+
+        // brewing hotbar, one line per recipe
+        let labels: Vec<String> = world.read_resource::<Recipes>().recipes
+            .iter()
+            .enumerate()
+            .map(|(slot, recipe)| recipe.label(slot))
+            .collect();
+        let mut recipe_entities = Vec::new();
+        for (slot, label) in labels.into_iter().enumerate() {
+            let recipe_transform = UiTransform::new(
+                format!("recipe_{}", slot), Anchor::TopLeft,
+                200.0, -100.0 - 40.0 * slot as f32, 1.0, 400.0, 40.0, 0
+            );
+            let recipe_entity = world
+                .create_entity()
+                .with(recipe_transform)
+                .with(UiText::new(
+                    font.clone(),
+                    label,
+                    [0.0, 0.0, 0.0, 1.0],
+                    30.0,
+                ))
+                .build();
+            recipe_entities.push(recipe_entity);
+        }
         
         world.add_resource(
             UiEntities {
@@ -444,6 +693,11 @@ impl LevelState {
                 game_over_entity,
                 mushroom_entity,
                 hornwort_entity,
+                recipe_entities,
+                boss_name_entity,
+                boss_bar_entity,
+                boss_fill_entity,
+                wave_entity,
             }
         );
     }
@@ -474,7 +728,31 @@ impl LevelState {
         )
     }
 
+    fn initialize_trajectory(world: &mut World, sprite_sheet: SpriteSheetHandle) {
+        let potion_sprite = world.read_resource::<Prefabs>().get("potion").sprite.unwrap_or(0);
+        let mut dots = Vec::new();
+        for _ in 0..TRAJECTORY_DOTS {
+            let mut transform = Transform::default();
+            transform.set_scale(0.4, 0.4, 1.0);
+            let dot = world
+                .create_entity()
+                .with(transform)
+                .with(SpriteRender {
+                    sprite_sheet: sprite_sheet.clone(),
+                    sprite_number: potion_sprite,
+                })
+                .with(Hidden)
+                .build();
+            dots.push(dot);
+        }
+        world.add_resource(TrajectoryDots { dots });
+    }
+
     fn initialize_camera(world: &mut World) {
+        let camera_config_path =
+            format!("{}/resources/camera.ron", application_root_dir());
+        world.add_resource(CameraConfig::load(&camera_config_path));
+
         let mut transform = Transform::default();
         transform.set_z(1.0);
         world
@@ -488,6 +766,79 @@ impl LevelState {
             .with(transform)
             .build();
     }
+
+    // each layer gets a one-sprite sheet of its own texture,
+    // ParallaxSystem places and tiles them around the camera
+    fn initialize_background(world: &mut World, name: &str) {
+        for layer in Backgrounds::load().layers(name) {
+            let sprite_sheet = {
+                let loader = world.read_resource::<Loader>();
+                let texture = loader.load(
+                    layer.texture.as_str(),
+                    PngFormat,
+                    TextureMetadata::srgb_scale(),
+                    (),
+                    &world.read_resource::<AssetStorage<Texture>>(),
+                );
+                let sprite = Sprite::from_pixel_values(
+                    layer.width, layer.height, layer.width, layer.height, 0, 0, [0.0, 0.0]);
+                loader.load_from_data(
+                    SpriteSheet { texture, sprites: vec![sprite] },
+                    (),
+                    &world.read_resource::<AssetStorage<SpriteSheet>>(),
+                )
+            };
+
+            world
+                .create_entity()
+                .with(ParallaxLayer {
+                    sprite_sheet,
+                    width: layer.width as f32,
+                    height: layer.height as f32,
+                    scroll: layer.scroll,
+                    offset: layer.offset,
+                    z: layer.z,
+                    tile_x: layer.tile_x,
+                    tile_y: layer.tile_y,
+                    tiles: Vec::new(),
+                })
+                .build();
+        }
+    }
+
+    // black bars for letterbox mode, sized by the HUD layout system
+    fn initialize_letterbox(world: &mut World) {
+        let black = {
+            let loader = world.read_resource::<Loader>();
+            let texture_storage =
+                world.read_resource::<AssetStorage<Texture>>();
+            loader.load_from_data(
+                TextureData::from([0.0, 0.0, 0.0, 1.0]),
+                (),
+                &texture_storage,
+            )
+        };
+
+        let bars = [
+            (LetterboxSide::Left, Anchor::MiddleLeft),
+            (LetterboxSide::Right, Anchor::MiddleRight),
+            (LetterboxSide::Top, Anchor::TopMiddle),
+            (LetterboxSide::Bottom, Anchor::BottomMiddle),
+        ];
+        for (side, anchor) in bars.iter() {
+            // below the HUD text, which sits at z 1
+            let bar_transform = UiTransform::new(
+                format!("letterbox_{:?}", side), *anchor,
+                0.0, 0.0, 0.5, 0.0, 0.0, 0
+            );
+            world
+                .create_entity()
+                .with(bar_transform)
+                .with(UiImage { texture: black.clone() })
+                .with(LetterboxBar { side: *side })
+                .build();
+        }
+    }
 }
 
 impl SimpleState for LevelState {
@@ -496,16 +847,30 @@ impl SimpleState for LevelState {
 
         let sprite_sheet_handle = LevelState::load_sprite_sheet(world);
         self.sprite_sheet = Some(sprite_sheet_handle.clone());
+        world.add_resource(sprite_sheet_handle.clone());
 
         world.add_resource(crate::NoMusic);
+        LevelState::initialize_settings(world);
+        LevelState::initialize_recipes(world);
+        LevelState::initialize_prefabs(world);
         LevelState::initialize_ui(world);
-        LevelState::create_entities(world, sprite_sheet_handle);
+        LevelState::initialize_difficulty(world);
+        LevelState::initialize_biome(world);
+        LevelState::create_entities(world, sprite_sheet_handle.clone());
+        let bounds = LevelBounds::from_platforms(world);
+        world.add_resource(bounds);
+        let navigation = NavGraph::from_platforms(world);
+        world.add_resource(navigation);
+        LevelState::initialize_trajectory(world, sprite_sheet_handle);
         LevelState::initialize_camera(world);
+        let background = world.read_resource::<Biome>().background.clone();
+        LevelState::initialize_background(world, &background);
+        LevelState::initialize_letterbox(world);
         LevelState::initialize_sound(world);
 
-        world.add_resource(PotionSpawner {
-            potion: std::option::Option::None,
-        });
+        world.add_resource(SpawnQueue::default());
+        world.add_resource(BossEncounter::default());
+        world.add_resource(WaveState::default());
     }
 
     fn handle_event(
@@ -525,66 +890,8 @@ impl SimpleState for LevelState {
         &mut self,
         data: &mut StateData<'_, GameData<'_, '_>>
     ) -> SimpleTrans {
-        let mut spawn = false;
-        let screen_dim = {
-            let dimensions = data.world.read_resource::<ScreenDimensions>();
-            (dimensions.width(), dimensions.height())
-        };
-        let potion_info = {
-            let mut spawner = data.world.write_resource::<PotionSpawner>();
-            match spawner.potion.take() {
-                Some(potion_info) => {
-                    spawn = true;
-                    potion_info
-                },
-                _ => PotionInfo::default(),
-            }
-        };
-
-        if spawn {
-            if let Some(sprite_sheet) = &self.sprite_sheet {
-                // spawn a potion
-                
-                let logical_mx = (potion_info.mx / screen_dim.0) * CAMERA_WIDTH;
-                let logical_my = (potion_info.my / screen_dim.1) * CAMERA_HEIGHT;
-
-                let world_mx = potion_info.px - CAMERA_WIDTH / 2.0 + logical_mx;
-                let world_my = potion_info.py + CAMERA_HEIGHT / 2.0 - logical_my;
-
-                let velocity = 
-                    Vector3::new(
-                        world_mx - potion_info.px, 
-                        world_my - potion_info.py, 0.0
-                    )
-                    .normalize();
-
-                let mut potion_transform = Transform::default();
-                potion_transform.set_xyz(
-                    potion_info.px,  // TODO tune spawn dist
-                    potion_info.py,
-                    0.0
-                );
-                data.world
-                    .create_entity()
-                    .with(potion_transform)
-                    .with(SpriteRender{
-                        sprite_sheet: sprite_sheet.clone(),
-                        sprite_number: 8,
-                    })
-                    .with(Mover{
-                        gravity: 0.5,
-                        velocity_x: velocity.x * POTION_SPEED,
-                        velocity_y: velocity.y * POTION_SPEED,
-                        jump_state: JumpState::Airborne,
-                        min_x: -100.0,
-                        max_x: LEVEL_WIDTH + 100.0,
-                    })
-                    .with(Potion{
-                        width: 8.0, // hacky way to manage our own collisions
-                        height: 9.0,
-                    })
-                    .build();
-            }
+        if let Some(sprite_sheet) = &self.sprite_sheet {
+            spawn_pending(data.world, sprite_sheet);
         }
         Trans::None
     }
@@ -595,14 +902,15 @@ impl SimpleState for LevelState {
 
 pub struct Player {
     pub in_hit: bool, // in hit state until hit_last
-    pub last_hit: f32,
+    pub aiming: bool, // throw held, potion flies on release
+    pub dead: bool, // out of health, removed after death_last
+    pub death_time: f32,
+    pub death_last: f32,
     pub last_throw: f32,
     pub last_heal: f32,
-    pub hit_cooldown: f32, // frequently you can get hit
     pub hit_last: f32,
     pub throw_cooldown: f32,
     pub heal_cooldown: f32,
-    pub health: i32,
 
     // Enum map would be nice for this purpose
     pub hornwort: usize,
@@ -613,11 +921,12 @@ impl Player {
     pub fn new() -> Player {
         Player {
             in_hit: false,
-            last_hit: 0.0,
+            aiming: false,
+            dead: false,
+            death_time: 0.0,
+            death_last: 1.0,
             last_throw: 0.0,
             last_heal: 0.0,
-            health: 100,
-            hit_cooldown: 1.0,
             heal_cooldown: 0.3,
             hit_last: 0.2,
             throw_cooldown: 0.5,
@@ -644,10 +953,11 @@ pub struct Mover {
     pub min_x: f32,
     pub max_x: f32,
     pub gravity: f32,
+    pub friction: f32, // slows sliding once landed, for things nobody steers
 }
 
 impl Mover {
-    fn new(min_x: f32, max_x: f32) -> Mover {
+    pub fn new(min_x: f32, max_x: f32) -> Mover {
         Mover {
             velocity_x: 0.0,
             velocity_y: 0.0,
@@ -655,6 +965,7 @@ impl Mover {
             min_x,
             max_x,
             gravity: 1.0,
+            friction: 0.0,
         }
     }
 }
@@ -671,6 +982,7 @@ impl Component for Platform {
 }
 
 // Rectangular collider to share
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Collider {
     pub width: f32,
     pub height: f32,
@@ -680,36 +992,40 @@ impl Component for Collider {
     type Storage = VecStorage<Self>;
 }
 
-// Animation Component
+// Animation Component, plays a state machine from resources/animations.ron
+#[derive(Debug, Clone)]
 pub struct SpriteAnimation {
-    pub elapsed_time: f32,
-    pub time_per_frame: f32,
-    pub move_offset: usize, 
-    pub move_count: usize,
-    pub idle_offset: usize,
-    pub idle_count: usize,
-    pub airborne_offset: usize,
+    pub machine: String,
+    pub state: String,
+    pub frame: usize,
+    pub elapsed_time: f32, // time spent on the current frame
+    pub finished: bool,
+    pub triggers: Vec<String>, // cleared every frame
 }
 
 impl SpriteAnimation {
-    fn new(move_offset: usize, move_count: usize, idle_offset: usize,
-           idle_count: usize, time_per_frame: f32, airborne_offset: usize) -> SpriteAnimation {
+    pub fn new(machine: &str, initial: &str) -> SpriteAnimation {
         SpriteAnimation {
-            move_count,
-            move_offset,
-            idle_count,
-            idle_offset,
-            time_per_frame,
-            airborne_offset,
+            machine: machine.to_string(),
+            state: initial.to_string(),
+            frame: 0,
             elapsed_time: 0.0,
+            finished: false,
+            triggers: Vec::new(),
         }
     }
+
+    // ask the machine to take a Trigger(name) transition this frame
+    pub fn trigger(&mut self, name: &str) {
+        self.triggers.push(name.to_string());
+    }
 }
 
 impl Component for SpriteAnimation {
     type Storage = VecStorage<Self>;
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum Ingredient {
     Hornwort{count: usize},
     Mushroom{count: usize},
@@ -719,16 +1035,205 @@ impl Component for Ingredient {
     type Storage = VecStorage<Self>;
 }
 
+// Makes enemies over time, see systems::spawner
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Spawner {
+    pub prefabs: Vec<String>, // one picked at random each time
+    pub max_alive: usize, // of ours out at once
+    pub cooldown: f32, // between spawns
+    pub range: f32, // wakes once the player is this close, 0 for always
+    pub budget: Option<usize>, // spawns left, None for no limit
+    #[serde(skip)]
+    pub elapsed: f32,
+    #[serde(skip)]
+    pub bounds: Option<(f32, f32)>, // passed on to what we spawn
+}
+
+impl Default for Spawner {
+    fn default() -> Spawner {
+        Spawner {
+            prefabs: Vec::new(),
+            max_alive: 1,
+            cooldown: 5.0,
+            range: 0.0,
+            budget: None,
+            elapsed: 0.0,
+            bounds: None,
+        }
+    }
+}
+
+impl Component for Spawner {
+    type Storage = VecStorage<Self>;
+}
+
+// Marks what a spawner made so it can count them
+pub struct SpawnedBy {
+    pub spawner: Entity,
+}
+
+impl Component for SpawnedBy {
+    type Storage = VecStorage<Self>;
+}
+
+// A multi-phase enemy that locks the gate, see systems::boss.
+// Also carries an Enemy for contact damage, loot and being hit
+#[derive(Debug, Clone)]
+pub struct Boss {
+    pub kind: BossKind,
+    pub home_x: f32, // arena is centred here
+    pub home_y: f32,
+    pub phase: usize,
+    pub next_pattern: usize,
+    pub attack: Option<BossPattern>, // running attack
+    pub state_time: f32,
+    pub direction: f32,
+}
+
+impl Boss {
+    pub fn new(kind: BossKind) -> Boss {
+        Boss {
+            kind,
+            home_x: 0.0,
+            home_y: 0.0,
+            phase: 0,
+            next_pattern: 0,
+            attack: None,
+            state_time: 0.0,
+            direction: -1.0,
+        }
+    }
+}
+
+impl Component for Boss {
+    type Storage = VecStorage<Self>;
+}
+
+// Enemies that fly instead of using a Mover, see systems::flying
+#[derive(Debug, Clone, Default)]
+pub struct Flyer {
+    pub home_x: f32,
+    pub home_y: f32,
+    pub velocity_x: f32,
+    pub velocity_y: f32,
+    pub elapsed_time: f32,
+}
+
+impl Flyer {
+    pub fn new(home_x: f32, home_y: f32) -> Flyer {
+        Flyer {
+            home_x,
+            home_y,
+            ..Flyer::default()
+        }
+    }
+}
+
+impl Component for Flyer {
+    type Storage = VecStorage<Self>;
+}
+
+// Fired by enemies, hurts the player and breaks on platforms
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Projectile {
+    pub width: f32,
+    pub height: f32,
+    pub damage: Damage,
+    #[serde(default)]
+    pub splash: Option<String>, // prefab spawned where it breaks
+}
+
+impl Component for Projectile {
+    type Storage = VecStorage<Self>;
+}
+
+// Shared by everything that can be hurt, hits arrive as DamageEvents
+#[derive(Debug, Clone)]
+pub struct Health {
+    pub current: i32,
+    pub max: i32,
+    pub last_hit: f32,
+    pub hit_cooldown: f32, // invulnerable this long after a hit
+    pub resistances: HashMap<DamageKind, f32>, // multiplies damage of a kind
+}
+
+impl Health {
+    pub fn new(max: i32, hit_cooldown: f32) -> Health {
+        Health {
+            current: max,
+            max,
+            last_hit: hit_cooldown, // not invulnerable on spawn
+            hit_cooldown,
+            resistances: HashMap::new(),
+        }
+    }
+
+    pub fn invulnerable(&self) -> bool {
+        self.last_hit < self.hit_cooldown
+    }
+
+    pub fn dead(&self) -> bool {
+        self.current <= 0
+    }
+}
+
+impl Component for Health {
+    type Storage = VecStorage<Self>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnemyState {
+    Patrol,
+    Notice,
+    Chase,
+    Windup,
+    Attack,
+    Cooldown,
+}
+
+#[derive(Debug, Clone)]
 pub struct Enemy {
+    pub kind: EnemyKind,
+    pub state: EnemyState,
+    pub state_time: f32, // seconds since entering state
+    pub unseen_time: f32, // seconds since the player was last seen
+    pub direction: f32, // 1 facing right, -1 left
+    pub leap: Option<NavEdge>, // platform link being followed, see navigation
+    pub leap_time: f32,
+    pub stranded: bool, // a leap gave up mid-air, confine us once we land
+}
+
+impl Enemy {
+    pub fn new(kind: EnemyKind) -> Enemy {
+        Enemy {
+            kind,
+            state: EnemyState::Patrol,
+            state_time: 0.0,
+            unseen_time: 0.0,
+            direction: 1.0,
+            leap: None,
+            leap_time: 0.0,
+            stranded: false,
+        }
+    }
+
+    pub fn enter(&mut self, state: EnemyState) {
+        self.state = state;
+        self.state_time = 0.0;
+    }
 }
 
 impl Component for Enemy {
     type Storage = VecStorage<Self>;
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Potion {
     pub width: f32,
     pub height: f32,
+    #[serde(default)]
+    pub damage: Damage, // set from the recipe when thrown
 }
 
 impl Component for Potion {
@@ -736,8 +1241,94 @@ impl Component for Potion {
 }
 
 pub struct Gate {
+    pub locked: bool, // while a boss guarding it is alive
 }
 
 impl Component for Gate {
     type Storage = VecStorage<Self>;
-}
\ No newline at end of file
+}
+
+// Spawns Particle entities, templates come from resources/particles.ron
+#[derive(Debug, Clone)]
+pub struct ParticleEmitter {
+    pub sprite: usize,
+    pub tint: Rgba,
+    pub scale: f32,
+    pub burst: usize,
+    pub rate: f32,
+    pub duration: f32, // negative to emit forever
+    pub lifetime: f32,
+    pub speed: (f32, f32),
+    pub angle: (f32, f32),
+    pub gravity: f32,
+    pub spread_x: f32,
+    pub elapsed_time: f32,
+    pub pending: f32, // fractional particles owed by rate
+    pub burst_done: bool,
+}
+
+impl Component for ParticleEmitter {
+    type Storage = VecStorage<Self>;
+}
+
+pub struct Particle {
+    pub velocity_x: f32,
+    pub velocity_y: f32,
+    pub gravity: f32,
+    pub age: f32,
+    pub lifetime: f32,
+    pub alpha: f32,
+}
+
+impl Component for Particle {
+    type Storage = VecStorage<Self>;
+}
+
+// Layout a UI element was created with at UI_WIDTH x UI_HEIGHT,
+// recorded the first time the HUD layout system sees it
+pub struct HudElement {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub font_size: Option<f32>,
+}
+
+impl Component for HudElement {
+    type Storage = VecStorage<Self>;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum LetterboxSide {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+// Covers window space outside the visible view in letterbox mode
+pub struct LetterboxBar {
+    pub side: LetterboxSide,
+}
+
+impl Component for LetterboxBar {
+    type Storage = VecStorage<Self>;
+}
+
+// A background layer scrolling at a fraction of the camera's speed,
+// drawn with as many tile entities as it takes to cover the view
+pub struct ParallaxLayer {
+    pub sprite_sheet: SpriteSheetHandle,
+    pub width: f32,
+    pub height: f32,
+    pub scroll: (f32, f32),
+    pub offset: (f32, f32),
+    pub z: f32,
+    pub tile_x: bool,
+    pub tile_y: bool,
+    pub tiles: Vec<Entity>,
+}
+
+impl Component for ParallaxLayer {
+    type Storage = VecStorage<Self>;
+}
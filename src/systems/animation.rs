@@ -1,9 +1,11 @@
 use amethyst::{
     core::timing::Time,
-    ecs::{Join, Read, System, WriteStorage, ReadStorage, Entities},
+    ecs::{Join, Read, System, WriteStorage, ReadStorage, ReadExpect, Entities, Entity, Write},
     renderer::{SpriteRender, Flipped},
+    shrev::EventChannel,
 };
 
+use crate::animations::{AnimationEvent, Animations, Condition};
 use crate::states::SpriteAnimation;
 use crate::states::Mover;
 use crate::states::JumpState;
@@ -11,6 +13,39 @@ use crate::states::JumpState;
 pub struct SpriteAnimationSystem {
 }
 
+// Whether a condition holds, entities without a mover only see triggers and Finished
+fn holds(condition: &Condition, animation: &SpriteAnimation, mover: Option<&Mover>) -> bool {
+    match condition {
+        Condition::Finished => animation.finished,
+        Condition::Trigger(name) => animation.triggers.iter().any(|trigger| trigger == name),
+        _ => match mover {
+            Some(mover) => {
+                let airborne = match mover.jump_state {
+                    JumpState::Landed => false,
+                    _ => true,
+                };
+                match condition {
+                    Condition::Airborne => airborne,
+                    Condition::Landed => !airborne,
+                    Condition::Moving => !airborne && mover.velocity_x.abs() > 0.0,
+                    Condition::Idle => !airborne && mover.velocity_x == 0.0,
+                    _ => false,
+                }
+            },
+            None => false,
+        },
+    }
+}
+
+fn publish(channel: &mut EventChannel<AnimationEvent>, entity: Entity, events: &[String]) {
+    for name in events {
+        channel.single_write(AnimationEvent {
+            entity,
+            name: name.clone(),
+        });
+    }
+}
+
 impl<'s> System<'s> for SpriteAnimationSystem {
     type SystemData = (
         Entities<'s>,
@@ -18,13 +53,16 @@ impl<'s> System<'s> for SpriteAnimationSystem {
         WriteStorage<'s, SpriteRender>,
         WriteStorage<'s, Flipped>,
         ReadStorage<'s, Mover>,
+        ReadExpect<'s, Animations>,
         Read<'s, Time>,
+        Write<'s, EventChannel<AnimationEvent>>,
     );
 
     fn run(&mut self, 
-           (entities, mut sprite_animations, mut sprite_renders, mut flipped_components, movers, time): Self::SystemData) {
-        for (e, animation, mover, render) in 
-            (&*entities, &mut sprite_animations, &movers, &mut sprite_renders).join() {
+           (entities, mut sprite_animations, mut sprite_renders, mut flipped_components, movers,
+            animations, time, mut animation_events): Self::SystemData) {
+        for (e, _animation, mover) in 
+            (&*entities, &sprite_animations, &movers).join() {
             
             if mover.velocity_x < 0.0 {
                 flipped_components.insert(e, Flipped::Horizontal).unwrap();
@@ -34,22 +72,59 @@ impl<'s> System<'s> for SpriteAnimationSystem {
             }
         }
 
-        for (animation, render, mover) in 
-            (&mut sprite_animations, &mut sprite_renders, &movers).join() {
-
-            animation.elapsed_time += time.delta_seconds();
-
-            if let JumpState::Airborne = mover.jump_state {
-                render.sprite_number = animation.airborne_offset;
-            } else if mover.velocity_x.abs() > 0.0 {
-                let frame = (animation.elapsed_time / animation.time_per_frame) as usize
-                    % animation.move_count;
-                render.sprite_number = animation.move_offset + frame;
-            } else {
-                let frame = (animation.elapsed_time / animation.time_per_frame) as usize
-                    % animation.idle_count;
-                render.sprite_number = animation.idle_offset + frame;
+        for (e, animation, render) in 
+            (&*entities, &mut sprite_animations, &mut sprite_renders).join() {
+            let machine = animations.machine(&animation.machine);
+
+            // advance the current clip
+            {
+                let clip = animations.clip(machine.clip_name(&animation.state));
+                animation.elapsed_time += time.delta_seconds();
+                while !animation.finished &&
+                    animation.elapsed_time >= clip.frames[animation.frame].duration {
+                    animation.elapsed_time -= clip.frames[animation.frame].duration;
+                    animation.frame += 1;
+                    if animation.frame >= clip.frames.len() {
+                        if clip.looping {
+                            animation.frame = 0;
+                        } else {
+                            animation.frame = clip.frames.len() - 1;
+                            animation.finished = true;
+                            break;
+                        }
+                    }
+                    publish(&mut animation_events, e, &clip.frames[animation.frame].events);
+                    if clip.frames[animation.frame].duration <= 0.0 {
+                        break;
+                    }
+                }
             }
+
+            // take the first transition that applies
+            let mover = movers.get(e);
+            let next = machine.transitions.iter()
+                .find(|transition| {
+                    transition.to != animation.state &&
+                    (transition.from.is_empty() || transition.from.contains(&animation.state)) &&
+                    holds(&transition.when, &*animation, mover)
+                })
+                .map(|transition| transition.to.clone());
+            let clip = match next {
+                Some(state) => {
+                    animation.state = state;
+                    animation.frame = 0;
+                    animation.elapsed_time = 0.0;
+                    animation.finished = false;
+
+                    let clip = animations.clip(machine.clip_name(&animation.state));
+                    publish(&mut animation_events, e, &clip.frames[0].events);
+                    clip
+                },
+                None => animations.clip(machine.clip_name(&animation.state)),
+            };
+            animation.triggers.clear();
+
+            render.sprite_number = clip.frames[animation.frame].sprite;
         }
     }
 }
@@ -0,0 +1,281 @@
+use amethyst::{
+    core::timing::Time,
+    core::Transform,
+    ecs::{Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage},
+    ecs::world::EntitiesRes,
+    renderer::{Hidden, Rgba},
+    ui::{UiText, UiTransform},
+};
+
+use crate::bosses::BossPattern;
+use crate::particles::ParticleEffects;
+use crate::spawn::{default_bounds, open_bounds, SpawnQueue, SpawnRequest};
+use crate::states::Boss;
+use crate::states::BossEncounter;
+use crate::states::CameraEffects;
+use crate::states::Gate;
+use crate::states::Health;
+use crate::states::HudElement;
+use crate::states::Mover;
+use crate::states::ParticleEmitter;
+use crate::states::Player;
+use crate::states::SpriteAnimation;
+use crate::states::UiEntities;
+use crate::states::BOSS_BAR_WIDTH;
+
+// how long the boss holds still while casting a volley or summon
+const CAST_TIME: f32 = 0.6;
+// where volleys leave the boss, above its centre
+const VOLLEY_OFFSET_Y: f32 = 15.0;
+const LOCKED_TINT: Rgba = Rgba(0.4, 0.4, 0.4, 1.0);
+// camera kicks for the fight starting, each new phase and the boss going down
+const START_TRAUMA: f32 = 0.4;
+const PHASE_TRAUMA: f32 = 0.6;
+const PHASE_STOP: f32 = 0.15;
+const DEFEAT_TRAUMA: f32 = 0.8;
+const DEFEAT_STOP: f32 = 0.3;
+
+// lift the arena: barriers down, bar hidden and the player free to go
+fn close_arena(encounter: &mut BossEncounter, entities: &EntitiesRes,
+               players: &ReadStorage<Player>, movers: &mut WriteStorage<Mover>,
+               hiddens: &mut WriteStorage<Hidden>, ui_entities: &UiEntities) {
+    encounter.active = false;
+    for barrier in encounter.barriers.drain(..) {
+        entities.delete(barrier).unwrap();
+    }
+    for (_, mover) in (players, movers).join() {
+        let (min_x, max_x) = default_bounds();
+        mover.min_x = min_x;
+        mover.max_x = max_x;
+    }
+    for ui in [ui_entities.boss_name_entity, ui_entities.boss_bar_entity,
+               ui_entities.boss_fill_entity].iter() {
+        hiddens.insert(*ui, Hidden).unwrap();
+    }
+}
+
+// Runs the boss fight: the arena closes when the player walks in, the
+// boss cycles its phase's attack patterns, and the gate stays locked
+// until the boss is dead
+pub struct BossSystem {
+}
+
+impl<'s> System<'s> for BossSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, Boss>,
+        ReadStorage<'s, Health>,
+        WriteStorage<'s, Mover>,
+        ReadStorage<'s, Player>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Gate>,
+        WriteStorage<'s, Rgba>,
+        WriteStorage<'s, ParticleEmitter>,
+        WriteStorage<'s, SpriteAnimation>,
+        WriteStorage<'s, Hidden>,
+        WriteStorage<'s, UiTransform>,
+        WriteStorage<'s, UiText>,
+        WriteStorage<'s, HudElement>,
+        ReadExpect<'s, UiEntities>,
+        ReadExpect<'s, ParticleEffects>,
+        Write<'s, BossEncounter>,
+        Write<'s, SpawnQueue>,
+        Write<'s, CameraEffects>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, (entities, mut bosses, healths, mut movers, players,
+           mut transforms, mut gates, mut tints, mut emitters, mut sprite_animations, mut hiddens,
+           mut ui_transforms, mut ui_texts, mut hud_elements, ui_entities, particles,
+           mut encounter, mut spawn_queue, mut camera_effects, time): Self::SystemData) {
+        let dt = time.delta_seconds();
+
+        let player = (&players, &transforms).join()
+            .filter(|(player, _)| !player.dead)
+            .map(|(_, transform)| (transform.translation().x, transform.translation().y))
+            .next();
+
+        // the gate opens once no boss is left
+        let boss_alive = (&bosses).join().next().is_some();
+        for (e, gate) in (&*entities, &mut gates).join() {
+            gate.locked = boss_alive;
+            if boss_alive {
+                tints.insert(e, LOCKED_TINT).unwrap();
+            } else {
+                tints.remove(e);
+            }
+        }
+
+        if encounter.active && !boss_alive {
+            close_arena(&mut encounter, &entities, &players, &mut movers, &mut hiddens, &ui_entities);
+            camera_effects.add_trauma(DEFEAT_TRAUMA);
+            camera_effects.hit_stop(DEFEAT_STOP);
+        }
+
+        for (e, boss, health) in (&*entities, &mut bosses, &healths).join() {
+            let (half_width, half_height) = (boss.kind.arena.0 / 2.0, boss.kind.arena.1 / 2.0);
+            let (x, y) = match transforms.get(e) {
+                Some(transform) => (transform.translation().x, transform.translation().y),
+                None => continue,
+            };
+
+            let inside = player.map_or(false, |(px, py)|
+                (px - boss.home_x).abs() < half_width && (py - boss.home_y).abs() < half_height);
+
+            // falling or getting knocked out of the arena calls the fight off
+            // until the player comes back, the walls only hold them sideways
+            if encounter.active && !inside && player.is_some() {
+                close_arena(&mut encounter, &entities, &players, &mut movers, &mut hiddens, &ui_entities);
+                boss.attack = None;
+                boss.state_time = 0.0;
+                continue;
+            }
+
+            // walking into the arena starts the fight
+            if !encounter.active {
+                if !inside {
+                    continue;
+                }
+                encounter.active = true;
+                encounter.min_x = boss.home_x - half_width;
+                encounter.max_x = boss.home_x + half_width;
+                for edge in [encounter.min_x, encounter.max_x].iter() {
+                    let mut transform = Transform::default();
+                    transform.set_xyz(*edge, boss.home_y - half_height, 0.5);
+                    let barrier = entities.build_entity()
+                        .with(transform, &mut transforms)
+                        .with(particles.get("arena_barrier").clone(), &mut emitters)
+                        .build();
+                    encounter.barriers.push(barrier);
+                }
+                for ui in [ui_entities.boss_name_entity, ui_entities.boss_bar_entity,
+                           ui_entities.boss_fill_entity].iter() {
+                    hiddens.remove(*ui);
+                }
+                if let Some(text) = ui_texts.get_mut(ui_entities.boss_name_entity) {
+                    text.text = boss.kind.name.clone();
+                }
+                if let Some(animation) = sprite_animations.get_mut(e) {
+                    animation.trigger("roar");
+                }
+                camera_effects.add_trauma(START_TRAUMA);
+            }
+
+            // keep the player inside
+            for (_, mover) in (&players, &mut movers).join() {
+                mover.min_x = encounter.min_x;
+                mover.max_x = encounter.max_x;
+            }
+
+            let fraction = (health.current as f32 / health.max as f32).max(0.0);
+            if let (Some(element), Some(transform)) = (hud_elements.get_mut(ui_entities.boss_fill_entity),
+                    ui_transforms.get_mut(ui_entities.boss_fill_entity)) {
+                // the layout system scales elements, so work in its units
+                let ui_scale = transform.height / element.height;
+                element.width = BOSS_BAR_WIDTH * fraction;
+                element.x = -(BOSS_BAR_WIDTH - element.width) / 2.0;
+                transform.width = element.width * ui_scale;
+                transform.local_x = element.x * ui_scale;
+            }
+
+            // moving to a new phase interrupts whatever the boss was doing
+            let phase = boss.kind.phase_for(fraction);
+            if phase != boss.phase {
+                boss.phase = phase;
+                boss.next_pattern = 0;
+                boss.attack = None;
+                boss.state_time = 0.0;
+                if let Some(animation) = sprite_animations.get_mut(e) {
+                    animation.trigger("roar");
+                }
+                camera_effects.add_trauma(PHASE_TRAUMA);
+                camera_effects.hit_stop(PHASE_STOP);
+            }
+            let phase = boss.kind.phases[boss.phase].clone();
+
+            let (player_x, player_y) = player.unwrap_or((x, y));
+            let toward = if player_x < x { -1.0 } else { 1.0 };
+            boss.state_time += dt;
+
+            let mover = match movers.get_mut(e) {
+                Some(mover) => mover,
+                None => continue,
+            };
+            let mut trigger = None;
+            match boss.attack.clone() {
+                None => {
+                    boss.direction = toward;
+                    mover.velocity_x = if (player_x - x).abs() > 10.0 {
+                        toward * phase.walk_speed
+                    } else {
+                        0.0
+                    };
+
+                    if boss.state_time > phase.cooldown && !phase.patterns.is_empty() {
+                        let pattern = phase.patterns[boss.next_pattern % phase.patterns.len()].clone();
+                        boss.next_pattern += 1;
+                        boss.state_time = 0.0;
+                        mover.velocity_x = 0.0;
+
+                        match pattern {
+                            BossPattern::Charge { .. } => trigger = Some("windup"),
+                            BossPattern::Volley { ref prefab, count, spread, speed } => {
+                                trigger = Some("cast");
+                                let (sx, sy) = (x, y + VOLLEY_OFFSET_Y);
+                                let aim = (player_y - sy).atan2(player_x - sx);
+                                for shot in 0..count {
+                                    let offset = if count > 1 {
+                                        spread * (shot as f32 / (count - 1) as f32 - 0.5)
+                                    } else {
+                                        0.0
+                                    };
+                                    let angle = aim + offset.to_radians();
+                                    let (min_x, max_x) = open_bounds();
+                                    spawn_queue.push(SpawnRequest::at(prefab, sx, sy)
+                                        .with_velocity(angle.cos() * speed, angle.sin() * speed)
+                                        .with_bounds(min_x, max_x));
+                                }
+                            },
+                            BossPattern::Summon { ref prefab, count } => {
+                                trigger = Some("cast");
+                                for helper in 0..count {
+                                    let side = if helper % 2 == 0 { -1.0 } else { 1.0 };
+                                    let spacing = 40.0 * (helper / 2 + 1) as f32;
+                                    spawn_queue.push(SpawnRequest::at(prefab,
+                                        x + side * spacing, y + 60.0));
+                                }
+                            },
+                        }
+                        boss.attack = Some(pattern);
+                    }
+                },
+                Some(BossPattern::Charge { windup, speed, duration }) => {
+                    if boss.state_time < windup {
+                        boss.direction = toward;
+                        mover.velocity_x = 0.0;
+                    } else if boss.state_time < windup + duration {
+                        if boss.state_time - dt < windup {
+                            trigger = Some("charge");
+                        }
+                        mover.velocity_x = boss.direction * speed;
+                    } else {
+                        mover.velocity_x = 0.0;
+                        boss.attack = None;
+                        boss.state_time = 0.0;
+                    }
+                },
+                Some(_) => {
+                    mover.velocity_x = 0.0;
+                    if boss.state_time > CAST_TIME {
+                        boss.attack = None;
+                        boss.state_time = 0.0;
+                    }
+                },
+            }
+
+            if let (Some(name), Some(animation)) = (trigger, sprite_animations.get_mut(e)) {
+                animation.trigger(name);
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use amethyst::ecs::{Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage};
+use amethyst::input::InputHandler;
+use amethyst::renderer::VirtualKeyCode;
+use amethyst::ui::UiText;
+
+use crate::recipes::{Brewing, Recipes};
+use crate::states::Player;
+use crate::states::UiEntities;
+
+const SLOT_KEYS: [VirtualKeyCode; 9] = [
+    VirtualKeyCode::Key1,
+    VirtualKeyCode::Key2,
+    VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4,
+    VirtualKeyCode::Key5,
+    VirtualKeyCode::Key6,
+    VirtualKeyCode::Key7,
+    VirtualKeyCode::Key8,
+    VirtualKeyCode::Key9,
+];
+
+const ACTIVE_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+const INACTIVE_COLOR: [f32; 4] = [0.3, 0.3, 0.3, 1.0];
+const UNAFFORDABLE_COLOR: [f32; 4] = [0.5, 0.5, 0.5, 0.4];
+
+pub struct BrewingSystem {
+}
+
+impl<'s> System<'s> for BrewingSystem {
+    type SystemData = (
+        ReadStorage<'s, Player>,
+        Read<'s, InputHandler<String, String>>,
+        ReadExpect<'s, Recipes>,
+        Write<'s, Brewing>,
+        ReadExpect<'s, UiEntities>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(&mut self, (players, input, recipes, mut brewing,
+           ui_entities, mut ui_texts): Self::SystemData) {
+        // pick the active throwable with the number keys
+        for (slot, key) in SLOT_KEYS.iter().enumerate() {
+            if slot < recipes.recipes.len() && input.key_is_down(*key) {
+                brewing.active = slot;
+            }
+        }
+
+        for player in (&players).join() {
+            for (slot, (recipe, entity)) in recipes.recipes.iter()
+                .zip(ui_entities.recipe_entities.iter()).enumerate() {
+                if let Some(text) = ui_texts.get_mut(*entity) {
+                    let label = recipe.label(slot);
+                    text.text = if slot == brewing.active {
+                        format!("> {}", label)
+                    } else {
+                        label
+                    };
+                    text.color = if !recipe.affordable(player) {
+                        UNAFFORDABLE_COLOR
+                    } else if slot == brewing.active {
+                        ACTIVE_COLOR
+                    } else {
+                        INACTIVE_COLOR
+                    };
+                }
+            }
+        }
+    }
+}
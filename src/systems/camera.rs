@@ -1,47 +1,245 @@
+use rand::Rng;
+
 use amethyst::core::Transform;
-use amethyst::ecs::{Join, Read, ReadStorage, System, WriteStorage};
-use amethyst::input::InputHandler;
-use amethyst::renderer::{VirtualKeyCode, Camera};
+use amethyst::core::timing::Time;
+use amethyst::ecs::{Entities, Entity, Join, ReadExpect, ReadStorage, System, Write, WriteStorage};
+use amethyst::renderer::{Camera, Projection, ScreenDimensions};
+use serde::{Deserialize, Serialize};
 
 use crate::states::Player;
 use crate::states::Mover;
+use crate::states::LevelBounds;
+use crate::states::CameraEffects;
+use crate::states::ViewSize;
 use crate::states::JumpState;
 use crate::states::CAMERA_HEIGHT;
 use crate::states::CAMERA_WIDTH;
 
-pub struct CameraSystem {
+// What to do with window space beyond the CAMERA_WIDTH x CAMERA_HEIGHT view
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum ScaleMode {
+    Expand, // show more of the world
+    Letterbox, // cover it with bars
+}
+
+// Loaded from resources/camera.ron
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CameraConfig {
+    pub scale_mode: ScaleMode,
+    // only scale the pixel art by whole numbers
+    pub pixel_perfect: bool,
+    // the player can move this far around the centre before the camera follows
+    pub deadzone_width: f32,
+    pub deadzone_height: f32,
+    // how quickly the camera catches up, higher is snappier
+    pub smoothing: f32,
+    // how far ahead to look in the facing direction
+    pub look_ahead_x: f32,
+    // how far below to look when falling faster than fall_speed
+    pub look_ahead_fall: f32,
+    pub fall_speed: f32,
+    // extra room shown around the level's platforms
+    pub bounds_padding: f32,
+    // screen shake offset at full trauma and how fast trauma wears off
+    pub max_shake: f32,
+    pub trauma_decay: f32,
+    // how quickly zoom punches ease back out
+    pub zoom_recovery: f32,
+    // time scale while a hit-stop is running
+    pub hit_stop_scale: f32,
+}
+
+impl Default for CameraConfig {
+    fn default() -> CameraConfig {
+        CameraConfig {
+            scale_mode: ScaleMode::Expand,
+            pixel_perfect: true,
+            deadzone_width: 40.0,
+            deadzone_height: 30.0,
+            smoothing: 6.0,
+            look_ahead_x: 50.0,
+            look_ahead_fall: 50.0,
+            fall_speed: 200.0,
+            bounds_padding: 30.0,
+            max_shake: 8.0,
+            trauma_decay: 1.5,
+            zoom_recovery: 10.0,
+            hit_stop_scale: 0.05,
+        }
+    }
+}
+
+// How much world fits in a window of the given size
+pub fn view_size(config: &CameraConfig, screen_width: f32, screen_height: f32) -> ViewSize {
+    let mut scale = (screen_width / CAMERA_WIDTH).min(screen_height / CAMERA_HEIGHT);
+    if config.pixel_perfect {
+        scale = scale.floor().max(1.0);
+    }
+    let width = screen_width / scale;
+    let height = screen_height / scale;
+    let (visible_width, visible_height) = match config.scale_mode {
+        ScaleMode::Expand => (width, height),
+        ScaleMode::Letterbox => (width.min(CAMERA_WIDTH), height.min(CAMERA_HEIGHT)),
+    };
+    ViewSize {
+        width,
+        height,
+        visible_width,
+        visible_height,
+        scale,
+    }
+}
+
+// move value toward target just enough to sit inside the deadzone
+fn outside_deadzone(value: f32, target: f32, half_size: f32) -> f32 {
+    if target > value + half_size {
+        target - half_size
+    } else if target < value - half_size {
+        target + half_size
+    } else {
+        value
+    }
+}
+
+// keep a view of half_view either side of focus inside min..max,
+// centring it if the level is smaller than the view
+fn clamp_to_bounds(focus: f32, min: f32, max: f32, half_view: f32) -> f32 {
+    if max - min <= half_view * 2.0 {
+        (min + max) / 2.0
+    } else {
+        focus.max(min + half_view).min(max - half_view)
+    }
 }
 
-struct TransformData {
-    x: f32,
-    y: f32,
+// landing faster than this shakes the camera
+const HARD_LANDING_SPEED: f32 = 350.0;
+const LANDING_TRAUMA: f32 = 0.3;
+
+#[derive(Default)]
+pub struct CameraSystem {
+    player: Option<Entity>,
+    focus: Option<(f32, f32)>, // world point at the centre of the screen
+    facing: f32,
+    last_velocity_y: f32,
+    slowed: bool,
 }
 
 impl<'s> System<'s> for CameraSystem {
     type SystemData = (
-        ReadStorage<'s, Camera>,
+        Entities<'s>,
+        WriteStorage<'s, Camera>,
         WriteStorage<'s, Transform>,
         ReadStorage<'s, Player>,
+        ReadStorage<'s, Mover>,
+        ReadExpect<'s, CameraConfig>,
+        ReadExpect<'s, LevelBounds>,
+        ReadExpect<'s, ScreenDimensions>,
+        Write<'s, ViewSize>,
+        Write<'s, CameraEffects>,
+        Write<'s, Time>,
     );
 
-    fn run (&mut self, (cameras, mut transforms, players): Self::SystemData) {
-        // TODO could be better implemented by caching player
-        let mut player_transform = None;
-        for (_player, transform) in (&players, &transforms).join() {
-            player_transform = Some(
-                TransformData{x: transform.translation().x, y: transform.translation().y});
+    fn run (&mut self, (entities, mut cameras, mut transforms, players, movers,
+            config, bounds, screen_dimensions, mut view, mut effects,
+            mut time): Self::SystemData) {
+        *view = view_size(&config, screen_dimensions.width(), screen_dimensions.height());
+
+        // effects run on real time so hit-stop doesn't slow its own recovery
+        let real_dt = time.delta_real_seconds();
+
+        if effects.hit_stop > 0.0 {
+            effects.hit_stop -= real_dt;
+            time.set_time_scale(config.hit_stop_scale);
+            self.slowed = true;
+        } else if self.slowed {
+            time.set_time_scale(1.0);
+            self.slowed = false;
         }
-        if let Some(transform_data) = player_transform {
 
-            let camera_x = transform_data.x;
-            let camera_y = transform_data.y;
+        // find the player again only when the cached one is gone
+        let cached = self.player
+            .filter(|player| entities.is_alive(*player) && players.get(*player).is_some());
+        self.player = cached.or_else(|| (&*entities, &players).join().map(|(e, _)| e).next());
+
+        let player_position = self.player.and_then(|player| transforms.get(player))
+            .map(|transform| (transform.translation().x, transform.translation().y));
+
+        let padding = config.bounds_padding;
 
-            for (_camera, transform) in (&cameras, &mut transforms).join() {
-                transform.set_xyz(camera_x - CAMERA_WIDTH / 2.0, camera_y - CAMERA_HEIGHT / 2.0, 1.0);
+        // follow the player, or hold still once they're gone
+        if let (Some(player), Some((player_x, player_y))) = (self.player, player_position) {
+            let mut target_x = player_x;
+            let mut target_y = player_y;
+            if let Some(mover) = movers.get(player) {
+                if mover.velocity_x > 0.0 {
+                    self.facing = 1.0;
+                } else if mover.velocity_x < 0.0 {
+                    self.facing = -1.0;
+                }
+                target_x += self.facing * config.look_ahead_x;
+                if mover.velocity_y < -config.fall_speed {
+                    target_y -= config.look_ahead_fall;
+                }
+
+                if let JumpState::Landed = mover.jump_state {
+                    if self.last_velocity_y < -HARD_LANDING_SPEED {
+                        effects.add_trauma(LANDING_TRAUMA);
+                    }
+                }
+                self.last_velocity_y = mover.velocity_y;
             }
 
+            let (focus_x, focus_y) = match self.focus {
+                Some((focus_x, focus_y)) => {
+                    let desired_x = outside_deadzone(focus_x, target_x, config.deadzone_width / 2.0);
+                    let desired_y = outside_deadzone(focus_y, target_y, config.deadzone_height / 2.0);
+                    // exponential smoothing, frame rate independent
+                    let blend = 1.0 - (-config.smoothing * time.delta_seconds()).exp();
+                    (focus_x + (desired_x - focus_x) * blend,
+                     focus_y + (desired_y - focus_y) * blend)
+                },
+                None => (player_x, player_y), // snap on the first frame
+            };
+            let focus_x = clamp_to_bounds(focus_x, bounds.min_x - padding,
+                bounds.max_x + padding, view.visible_width / 2.0);
+            let focus_y = clamp_to_bounds(focus_y, bounds.min_y - padding,
+                bounds.max_y + padding, view.visible_height / 2.0);
+            self.focus = Some((focus_x, focus_y));
+        }
+
+        let (focus_x, focus_y) = match self.focus {
+            Some(focus) => focus,
+            None => return,
+        };
+
+        // zoom punches shrink the view and ease back out
+        let view_scale = 1.0 - effects.zoom.min(0.5);
+        let view_width = view.width * view_scale;
+        let view_height = view.height * view_scale;
+        effects.zoom *= (-config.zoom_recovery * real_dt).exp();
+
+        // trauma squared keeps small knocks subtle
+        let mut rng = rand::thread_rng();
+        let shake = effects.trauma * effects.trauma * config.max_shake;
+        let shake_x = shake * rng.gen_range(-1.0, 1.0);
+        let shake_y = shake * rng.gen_range(-1.0, 1.0);
+        effects.trauma = (effects.trauma - config.trauma_decay * real_dt).max(0.0);
+
+        // the shaken, zoomed view still has to stay inside the level
+        let centre_x = clamp_to_bounds(focus_x + shake_x, bounds.min_x - padding,
+            bounds.max_x + padding, view.visible_width * view_scale / 2.0);
+        let centre_y = clamp_to_bounds(focus_y + shake_y, bounds.min_y - padding,
+            bounds.max_y + padding, view.visible_height * view_scale / 2.0);
+
+        for (camera, transform) in (&mut cameras, &mut transforms).join() {
+            *camera = Camera::from(Projection::orthographic(
+                0.0,
+                view_width,
+                0.0,
+                view_height,
+            ));
+            transform.set_xyz(centre_x - view_width / 2.0, centre_y - view_height / 2.0, 1.0);
         }
     }
 
 }
-
@@ -0,0 +1,67 @@
+use amethyst::core::nalgebra::Vector4;
+use amethyst::core::Transform;
+use amethyst::ecs::{Join, Read, ReadExpect, ReadStorage, System, Write};
+use amethyst::input::InputHandler;
+use amethyst::renderer::{ActiveCamera, Camera, ScreenDimensions};
+
+use crate::states::Cursor;
+
+// Convert a position in screen pixels to world coordinates through the
+// camera's projection and transform. Returns None if the projection can't be inverted
+pub fn screen_to_world(sx: f32, sy: f32, camera: &Camera, camera_transform: &Transform,
+                       screen_dimensions: &ScreenDimensions) -> Option<(f32, f32)> {
+    // screen -> normalized device coordinates, screen y grows downwards
+    let ndc_x = 2.0 * sx / screen_dimensions.width() - 1.0;
+    let ndc_y = 1.0 - 2.0 * sy / screen_dimensions.height();
+
+    // view is the inverse of the camera transform, so undoing
+    // projection * view is transform * projection^-1
+    let inverse_projection = camera.proj.try_inverse()?;
+    let world = camera_transform.matrix() * inverse_projection
+        * Vector4::new(ndc_x, ndc_y, 0.0, 1.0);
+
+    if world.w == 0.0 {
+        return None;
+    }
+    Some((world.x / world.w, world.y / world.w))
+}
+
+pub struct CursorSystem {
+}
+
+impl<'s> System<'s> for CursorSystem {
+    type SystemData = (
+        ReadStorage<'s, Camera>,
+        ReadStorage<'s, Transform>,
+        Option<Read<'s, ActiveCamera>>,
+        ReadExpect<'s, ScreenDimensions>,
+        Read<'s, InputHandler<String, String>>,
+        Write<'s, Cursor>,
+    );
+
+    fn run(&mut self, (cameras, transforms, active_camera, screen_dimensions,
+           input, mut cursor): Self::SystemData) {
+        let mouse_position = match input.mouse_position() {
+            Some(pos) => pos,
+            None => return,
+        };
+
+        // prefer the active camera, otherwise use the first one around
+        let camera = active_camera
+            .and_then(|active| {
+                let entity = active.entity;
+                cameras.get(entity).and_then(|camera| {
+                    transforms.get(entity).map(|transform| (camera, transform))
+                })
+            })
+            .or_else(|| (&cameras, &transforms).join().next());
+
+        if let Some((camera, transform)) = camera {
+            if let Some((x, y)) = screen_to_world(mouse_position.0 as f32,
+                mouse_position.1 as f32, camera, transform, &screen_dimensions) {
+                cursor.x = x;
+                cursor.y = y;
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use rand::Rng;
+
+use amethyst::{
+    core::Transform,
+    core::timing::Time,
+    ecs::{Entities, Join, Read, ReadExpect, ReadStorage, Resources, System, SystemData,
+        Write, WriteStorage},
+    shrev::{EventChannel, ReaderId},
+};
+use amethyst::ui::UiText;
+
+use crate::damage::DamageEvent;
+use crate::states::Enemy;
+use crate::states::Health;
+use crate::states::Mover;
+use crate::states::Flyer;
+use crate::states::Player;
+use crate::states::SpriteAnimation;
+use crate::states::CameraEffects;
+use crate::states::UiEntities;
+use crate::states::UiValues;
+use crate::spawn::{SpawnQueue, SpawnRequest};
+
+// camera kick when the player gets hurt
+const PLAYER_HIT_TRAUMA: f32 = 0.5;
+const PLAYER_HIT_ZOOM: f32 = 0.1;
+const PLAYER_HIT_STOP: f32 = 0.08;
+
+// sideways and upward speed loot is thrown out with
+const LOOT_SPREAD: f32 = 60.0;
+const LOOT_POP: f32 = 150.0;
+
+// Applies DamageEvents to Health: resistances, invulnerability after a
+// hit and knockback away from the source. Enemies that run out of
+// health are removed here, the player's death is left to PlayerSystem
+pub struct DamageSystem {
+    pub reader: Option<ReaderId<DamageEvent>>,
+}
+
+impl<'s> System<'s> for DamageSystem {
+    type SystemData = (
+        Entities<'s>,
+        Read<'s, EventChannel<DamageEvent>>,
+        WriteStorage<'s, Health>,
+        WriteStorage<'s, Mover>,
+        WriteStorage<'s, Flyer>,
+        WriteStorage<'s, Player>,
+        ReadStorage<'s, Enemy>,
+        ReadStorage<'s, Transform>,
+        WriteStorage<'s, SpriteAnimation>,
+        Read<'s, Time>,
+        Write<'s, CameraEffects>,
+        Write<'s, SpawnQueue>,
+        ReadExpect<'s, UiEntities>,
+        Write<'s, UiValues>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.reader = Some(res.fetch_mut::<EventChannel<DamageEvent>>().register_reader());
+    }
+
+    fn run(&mut self, (entities, damage_events, mut healths, mut movers, mut flyers, mut players,
+           enemies, transforms, mut sprite_animations, time, mut camera_effects,
+           mut spawn_queue, ui_entities, mut ui_values, mut ui_texts): Self::SystemData) {
+        let mut rng = rand::thread_rng();
+        let dt = time.delta_seconds();
+        for health in (&mut healths).join() {
+            health.last_hit += dt;
+        }
+
+        for event in damage_events.read(self.reader.as_mut().unwrap()) {
+            let health = match healths.get_mut(event.target) {
+                Some(health) => health,
+                None => continue,
+            };
+            if health.dead() || health.invulnerable() {
+                continue;
+            }
+
+            let resistance = health.resistances.get(&event.damage.kind).cloned().unwrap_or(1.0);
+            health.current -= (event.damage.amount as f32 * resistance).round() as i32;
+            health.last_hit = 0.0;
+
+            let (x, y) = match transforms.get(event.target) {
+                Some(transform) => (transform.translation().x, transform.translation().y),
+                None => (event.source_x, event.source_y),
+            };
+            // instantaneous velocity away from whatever hit us
+            let (dx, dy) = (x - event.source_x, y - event.source_y);
+            let length = (dx * dx + dy * dy).sqrt();
+            if event.damage.knockback > 0.0 && length > 0.0 {
+                let (velocity_x, velocity_y) = (dx / length * event.damage.knockback,
+                    dy / length * event.damage.knockback);
+                if let Some(mover) = movers.get_mut(event.target) {
+                    mover.velocity_x = velocity_x;
+                    mover.velocity_y = velocity_y;
+                }
+                if let Some(flyer) = flyers.get_mut(event.target) {
+                    flyer.velocity_x = velocity_x;
+                    flyer.velocity_y = velocity_y;
+                }
+            }
+            if let Some(animation) = sprite_animations.get_mut(event.target) {
+                animation.trigger("hurt");
+            }
+
+            if let Some(player) = players.get_mut(event.target) {
+                player.in_hit = true;
+                camera_effects.add_trauma(PLAYER_HIT_TRAUMA);
+                camera_effects.zoom_punch(PLAYER_HIT_ZOOM);
+                camera_effects.hit_stop(PLAYER_HIT_STOP);
+            }
+
+            if health.dead() && enemies.get(event.target).is_some() {
+                if let Some(text) = ui_texts.get_mut(ui_entities.score_entity) {
+                    ui_values.score += 1;
+                    text.text = format!("SCORE: {}", ui_values.score).to_string();
+                }
+                spawn_queue.push(SpawnRequest::at("enemy_puff", x, y));
+
+                // loot pops up and falls onto the platforms
+                let enemy = enemies.get(event.target).unwrap();
+                for drop in enemy.kind.loot.iter() {
+                    if rng.gen::<f32>() >= drop.chance {
+                        continue;
+                    }
+                    for _ in 0..drop.count {
+                        spawn_queue.push(SpawnRequest::at(&drop.prefab, x, y)
+                            .with_velocity(rng.gen_range(-LOOT_SPREAD, LOOT_SPREAD), LOOT_POP));
+                    }
+                }
+                entities.delete(event.target).unwrap();
+            }
+        }
+    }
+}
@@ -1,17 +1,23 @@
 use amethyst::{
     core::timing::Time,
     core::Transform,
-    ecs::{Join, Read, System, WriteStorage, ReadStorage, Entities, ReadExpect},
-    audio::{output::Output, Source},
-    assets::AssetStorage,
+    ecs::{Join, Read, ReadExpect, System, WriteStorage, ReadStorage, Entities, Write},
+    shrev::EventChannel,
 };
 
+use crate::states::Boss;
 use crate::states::Enemy;
+use crate::states::EnemyState;
 use crate::states::Player;
 use crate::states::Mover;
+use crate::states::JumpState;
+use crate::states::Platform;
 use crate::states::Collider;
-use crate::states::SoundEffects;
-use crate::collision::check_collision;
+use crate::states::SpriteAnimation;
+use crate::damage::DamageEvent;
+use crate::spawn::{default_bounds, open_bounds, SpawnQueue, SpawnRequest};
+use crate::navigation::{NavGraph, NavLink, NavNode};
+use crate::collision::{check_collision, segment_hits};
 
 pub struct EnemySystem {
 }
@@ -20,50 +26,232 @@ pub const SWAP_RANGE: f32 = 1.0;
 
 pub const ENEMY_VELOCITY: f32 = 50.0;
 
+// where projectiles leave a ranged enemy, relative to its centre
+const PROJECTILE_OFFSET_X: f32 = 8.0;
+const PROJECTILE_OFFSET_Y: f32 = 4.0;
+
+// give up on a jump or drop that hasn't landed us somewhere new
+const LEAP_TIMEOUT: f32 = 3.0;
+
+// keep a mover on one platform
+fn confine(mover: &mut Mover, node: &NavNode, half_width: f32) {
+    mover.min_x = node.left + half_width;
+    mover.max_x = node.right - half_width;
+}
+
 impl<'s> System<'s> for EnemySystem {
     type SystemData = (
-        ReadStorage<'s, Enemy>,
+        WriteStorage<'s, Enemy>,
         ReadStorage<'s, Transform>,
         ReadStorage<'s, Collider>,
-        WriteStorage<'s, Player>,
+        ReadStorage<'s, Platform>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Boss>,
         WriteStorage<'s, Mover>,
-        Read<'s, AssetStorage<Source>>,
-        ReadExpect<'s, SoundEffects>,
-        Option<Read<'s, Output>>,
+        WriteStorage<'s, SpriteAnimation>,
+        Entities<'s>,
+        Read<'s, Time>,
+        Write<'s, EventChannel<DamageEvent>>,
+        Write<'s, SpawnQueue>,
+        ReadExpect<'s, NavGraph>,
     );
 
-    fn run(&mut self, (enemies, transforms, colliders, mut players, mut movers,
-        audio_source, sound_effects, audio_output): Self::SystemData) {
-        for (enemy, enemy_transform, mover) in (&enemies, &transforms, &mut movers).join() {
-            if ((enemy_transform.translation().x - mover.max_x).abs() < SWAP_RANGE && mover.velocity_x > 0.0) ||
-               ((enemy_transform.translation().x - mover.min_x).abs() < SWAP_RANGE && mover.velocity_x < 0.0) {
-               //
-               mover.velocity_x = mover.velocity_x * -1.0;
+    fn run(&mut self, (mut enemies, transforms, colliders, platforms, players, bosses,
+        mut movers, mut sprite_animations,
+        entities, time, mut damage_events, mut spawn_queue, navigation): Self::SystemData) {
+        let dt = time.delta_seconds();
+
+        let target = (&players, &transforms).join()
+            .filter(|(player, _)| !player.dead)
+            .map(|(_, transform)| (transform.translation().x, transform.translation().y))
+            .next();
+        let target_platform = (&players, &colliders, &transforms).join()
+            .filter(|(player, _, _)| !player.dead)
+            .filter_map(|(_, collider, transform)| {
+                let translation = transform.translation();
+                navigation.platform_at(translation.x, translation.y - collider.height / 2.0)
+            })
+            .next();
+
+        // bosses keep Enemy for contact damage and loot but run their own AI
+        for (ee, enemy, enemy_transform, mover, _) in
+            (&*entities, &mut enemies, &transforms, &mut movers, !&bosses).join() {
+            let x = enemy_transform.translation().x;
+            let y = enemy_transform.translation().y;
+            enemy.state_time += dt;
+
+            let (feet, half_width) = colliders.get(ee).map_or((y, 0.0), |collider|
+                (y - collider.height / 2.0, collider.width / 2.0));
+            let landed = if let JumpState::Landed = mover.jump_state { true } else { false };
+            let platform = if landed { navigation.platform_at(x, feet) } else { None };
+
+            // a jump or drop plays out before the state machine carries on
+            if let Some(leap) = enemy.leap.clone() {
+                enemy.leap_time += dt;
+                if platform.map_or(false, |node| node != leap.from) || enemy.leap_time > LEAP_TIMEOUT {
+                    enemy.leap = None;
+                    match platform {
+                        Some(node) => confine(mover, &navigation.nodes[node], half_width),
+                        None => enemy.stranded = true,
+                    }
+                } else if platform == Some(leap.from) {
+                    let across = (leap.landing_x - leap.takeoff_x).signum();
+                    enemy.direction = across;
+                    if (leap.takeoff_x - x) * across > SWAP_RANGE {
+                        mover.velocity_x = across * enemy.kind.chase_speed;
+                    } else {
+                        if leap.link == NavLink::Jump {
+                            mover.jump_state = JumpState::Jump;
+                        }
+                        mover.velocity_x = across * if leap.link == NavLink::Walk {
+                            enemy.kind.chase_speed
+                        } else {
+                            leap.speed
+                        };
+                    }
+                }
+                continue;
             }
-        }
-        for (enemy, e_collider, e_transform) in (&enemies, &colliders, &transforms).join() {
-            // check for collision, if so then check for e pressed and match on resource
-            for (player, player_collider, player_transform, mover) in (&mut players, &colliders, &transforms, &mut movers).join() {
-                if check_collision(&player_collider, &player_transform, &e_collider, &e_transform) &&
-                   player.last_hit > player.hit_cooldown {
-                    player.in_hit = true;
-                    player.last_hit = 0.0;
-                    player.health -= 40;
-
-                    if let Some(ref out_device) = audio_output.as_ref() {
-                        if let Some(sound) = audio_source.get(&sound_effects.hurt) {
-                            out_device.play_once(sound, 0.2);
+            if let (true, Some(node)) = (enemy.stranded, platform) {
+                confine(mover, &navigation.nodes[node], half_width);
+                enemy.stranded = false;
+            }
+
+            // chase onto the player's platform when there's a way there
+            if let (true, EnemyState::Chase, Some(here), Some(there)) =
+                (enemy.kind.pathfinding, enemy.state, platform, target_platform) {
+                let path = if here != there {
+                    navigation.find_path(here, x, there, enemy.kind.chase_speed)
+                } else {
+                    None
+                };
+                if let Some(leap) = path.and_then(|path| path.into_iter().next()) {
+                    let (min_x, max_x) = default_bounds();
+                    mover.min_x = min_x;
+                    mover.max_x = max_x;
+                    enemy.leap = Some(leap);
+                    enemy.leap_time = 0.0;
+                    continue;
+                }
+            }
+
+            // the player is seen when they're in range ahead of us
+            // and no platform is in the way
+            let seen = target.map_or(false, |(px, py)| {
+                let dx = px - x;
+                dx.abs() < enemy.kind.sight_range &&
+                    (py - y).abs() < enemy.kind.sight_height &&
+                    (dx * enemy.direction >= 0.0 || enemy.state != EnemyState::Patrol) &&
+                    !(&platforms, &colliders, &transforms).join()
+                        .any(|(_, collider, transform)|
+                            segment_hits(collider, transform, (x, y), (px, py)))
+            });
+            if seen {
+                enemy.unseen_time = 0.0;
+            } else {
+                enemy.unseen_time += dt;
+            }
+            let toward = target.map_or(enemy.direction, |(px, _)|
+                if px < x { -1.0 } else { 1.0 });
+
+            let mut trigger = None;
+            match enemy.state {
+                EnemyState::Patrol => {
+                    if mover.velocity_x != 0.0 {
+                        enemy.direction = mover.velocity_x.signum();
+                    }
+                    if ((x - mover.max_x).abs() < SWAP_RANGE && enemy.direction > 0.0) ||
+                       ((x - mover.min_x).abs() < SWAP_RANGE && enemy.direction < 0.0) {
+                        enemy.direction *= -1.0;
+                    }
+                    mover.velocity_x = enemy.direction * enemy.kind.patrol_speed;
+                    if seen {
+                        enemy.enter(EnemyState::Notice);
+                        trigger = Some("notice");
+                    }
+                },
+                EnemyState::Notice => {
+                    mover.velocity_x = 0.0;
+                    enemy.direction = toward;
+                    if enemy.state_time > enemy.kind.notice_time {
+                        enemy.enter(if seen { EnemyState::Chase } else { EnemyState::Patrol });
+                    }
+                },
+                EnemyState::Chase => {
+                    enemy.direction = toward;
+                    // chase within our platform, waiting at its edge
+                    let at_edge = (enemy.direction > 0.0 && x >= mover.max_x - SWAP_RANGE) ||
+                        (enemy.direction < 0.0 && x <= mover.min_x + SWAP_RANGE);
+                    mover.velocity_x = if at_edge { 0.0 } else { enemy.direction * enemy.kind.chase_speed };
+
+                    let in_reach = target.map_or(false, |(px, py)|
+                        (px - x).abs() < enemy.kind.attack_range &&
+                        (py - y).abs() < enemy.kind.sight_height);
+                    if seen && in_reach {
+                        enemy.enter(EnemyState::Windup);
+                        trigger = Some("windup");
+                    } else if enemy.unseen_time > enemy.kind.lose_time {
+                        enemy.enter(EnemyState::Patrol);
+                    }
+                },
+                EnemyState::Windup => {
+                    mover.velocity_x = 0.0;
+                    enemy.direction = toward;
+                    if enemy.state_time > enemy.kind.windup_time {
+                        enemy.enter(EnemyState::Attack);
+                        trigger = Some("attack");
+
+                        // ranged enemies fire straight at the player
+                        if let (Some(projectile), Some((px, py))) = (&enemy.kind.projectile, target) {
+                            let (sx, sy) = (x + enemy.direction * PROJECTILE_OFFSET_X,
+                                y + PROJECTILE_OFFSET_Y);
+                            let (dx, dy) = (px - sx, py - sy);
+                            let length = (dx * dx + dy * dy).sqrt().max(1.0);
+                            let speed = enemy.kind.projectile_speed;
+                            let (min_x, max_x) = open_bounds();
+                            spawn_queue.push(SpawnRequest::at(projectile, sx, sy)
+                                .with_velocity(dx / length * speed, dy / length * speed)
+                                .with_bounds(min_x, max_x));
                         }
                     }
+                },
+                EnemyState::Attack => {
+                    mover.velocity_x = if enemy.kind.projectile.is_some() {
+                        0.0
+                    } else {
+                        enemy.direction * enemy.kind.attack_speed
+                    };
+                    if enemy.state_time > enemy.kind.attack_time {
+                        enemy.enter(EnemyState::Cooldown);
+                    }
+                },
+                EnemyState::Cooldown => {
+                    mover.velocity_x = 0.0;
+                    if enemy.state_time > enemy.kind.cooldown_time {
+                        enemy.enter(if seen { EnemyState::Chase } else { EnemyState::Patrol });
+                    }
+                },
+            }
 
+            if let (Some(name), Some(animation)) = (trigger, sprite_animations.get_mut(ee)) {
+                animation.trigger(name);
+            }
+        }
 
-                    // instantaneous velocity
-                    let mut velocity = player_transform.translation() - e_transform.translation();
-                    velocity = velocity.normalize() * 100.0;
-                    mover.velocity_x = velocity.x;
-                    mover.velocity_y = velocity.y;
+        // touching an enemy hurts, DamageSystem sorts out invulnerability
+        for (enemy, e_collider, e_transform) in (&enemies, &colliders, &transforms).join() {
+            for (ep, player, player_collider, player_transform) in (&*entities, &players, &colliders, &transforms).join() {
+                if check_collision(&player_collider, &player_transform, &e_collider, &e_transform) &&
+                   !player.dead {
+                    let translation = e_transform.translation();
+                    damage_events.single_write(DamageEvent {
+                        target: ep,
+                        source_x: translation.x,
+                        source_y: translation.y,
+                        damage: enemy.kind.contact_damage.clone(),
+                    });
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
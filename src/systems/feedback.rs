@@ -0,0 +1,54 @@
+use amethyst::ecs::{Entities, Join, ReadStorage, System, WriteStorage};
+use amethyst::renderer::{Hidden, Rgba};
+
+use crate::states::Player;
+use crate::states::Health;
+use crate::states::SpriteAnimation;
+
+const FLASH_TIME: f32 = 0.1;
+const FLASH_TINT: Rgba = Rgba(1.0, 0.3, 0.3, 1.0);
+const BLINK_PERIOD: f32 = 0.08;
+
+// Visual damage feedback read off each Health's hit state: a tint flash
+// right after a hit, blinking for the rest of the invulnerability
+// window and the player's death animation once health runs out
+pub struct DamageFeedbackSystem {
+}
+
+impl<'s> System<'s> for DamageFeedbackSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Health>,
+        WriteStorage<'s, SpriteAnimation>,
+        WriteStorage<'s, Rgba>,
+        WriteStorage<'s, Hidden>,
+    );
+
+    fn run(&mut self, (entities, players, healths, mut sprite_animations, mut tints,
+           mut hiddens): Self::SystemData) {
+        for (e, health) in (&*entities, &healths).join() {
+            if players.get(e).map_or(false, |player| player.dead) {
+                tints.remove(e);
+                hiddens.remove(e);
+                if let Some(animation) = sprite_animations.get_mut(e) {
+                    animation.trigger("death");
+                }
+                continue;
+            }
+
+            if health.last_hit < FLASH_TIME {
+                tints.insert(e, FLASH_TINT).unwrap();
+            } else {
+                tints.remove(e);
+            }
+
+            let blink_off = (health.last_hit / BLINK_PERIOD) as usize % 2 == 1;
+            if health.invulnerable() && blink_off {
+                hiddens.insert(e, Hidden).unwrap();
+            } else {
+                hiddens.remove(e);
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use amethyst::{
+    core::timing::Time,
+    core::Transform,
+    ecs::{Entities, Join, Read, ReadStorage, System, WriteStorage},
+    renderer::Flipped,
+};
+
+use crate::collision::segment_hits;
+use crate::states::Collider;
+use crate::states::Enemy;
+use crate::states::EnemyState;
+use crate::states::Flyer;
+use crate::states::Platform;
+use crate::states::Player;
+use crate::states::SpriteAnimation;
+
+// how quickly a flyer steers toward where it wants to be
+const STEERING: f32 = 3.0;
+
+// Flying enemies run the same states as walkers but move freely in 2D:
+// drifting and bobbing around home while patrolling, hovering above the
+// player while chasing and swooping straight at them to attack
+pub struct FlyingSystem {
+}
+
+impl<'s> System<'s> for FlyingSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, Enemy>,
+        WriteStorage<'s, Flyer>,
+        WriteStorage<'s, Transform>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Platform>,
+        ReadStorage<'s, Collider>,
+        WriteStorage<'s, SpriteAnimation>,
+        WriteStorage<'s, Flipped>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, (entities, mut enemies, mut flyers, mut transforms, players,
+           platforms, colliders, mut sprite_animations, mut flipped_components,
+           time): Self::SystemData) {
+        let dt = time.delta_seconds();
+
+        let target = (&players, &transforms).join()
+            .filter(|(player, _)| !player.dead)
+            .map(|(_, transform)| (transform.translation().x, transform.translation().y))
+            .next();
+        let blockers: Vec<(Collider, Transform)> = (&platforms, &colliders, &transforms).join()
+            .map(|(_, collider, transform)| (collider.clone(), transform.clone()))
+            .collect();
+
+        for (e, enemy, flyer, transform) in
+            (&*entities, &mut enemies, &mut flyers, &mut transforms).join() {
+            let x = transform.translation().x;
+            let y = transform.translation().y;
+            let flight = enemy.kind.flight.clone().unwrap_or_default();
+            enemy.state_time += dt;
+            flyer.elapsed_time += dt;
+
+            let seen = target.map_or(false, |(px, py)| {
+                (px - x).abs() < enemy.kind.sight_range &&
+                    (py - y).abs() < enemy.kind.sight_height &&
+                    !blockers.iter().any(|(collider, transform)|
+                        segment_hits(collider, transform, (x, y), (px, py)))
+            });
+            if seen {
+                enemy.unseen_time = 0.0;
+            } else {
+                enemy.unseen_time += dt;
+            }
+            let (px, py) = target.unwrap_or((x, y));
+            let bob = flight.bob_height *
+                (flyer.elapsed_time * flight.bob_frequency * 2.0 * std::f32::consts::PI).sin();
+
+            // where the flyer wants to be and how fast it may get there,
+            // None while swooping on its own momentum
+            let mut trigger = None;
+            let goal = match enemy.state {
+                EnemyState::Patrol => {
+                    let drift = (flyer.elapsed_time * enemy.kind.patrol_speed /
+                        flight.patrol_range.max(1.0)).sin();
+                    if seen {
+                        enemy.enter(EnemyState::Notice);
+                    }
+                    Some((flyer.home_x + drift * flight.patrol_range, flyer.home_y + bob,
+                        enemy.kind.patrol_speed))
+                },
+                EnemyState::Notice => {
+                    if enemy.state_time > enemy.kind.notice_time {
+                        enemy.enter(if seen { EnemyState::Chase } else { EnemyState::Patrol });
+                    }
+                    Some((x, y, enemy.kind.patrol_speed))
+                },
+                EnemyState::Chase => {
+                    if seen && (px - x).abs() < enemy.kind.attack_range {
+                        enemy.enter(EnemyState::Windup);
+                        trigger = Some("windup");
+                    } else if enemy.unseen_time > enemy.kind.lose_time {
+                        enemy.enter(EnemyState::Patrol);
+                    }
+                    Some((px, py + flight.hover_height + bob, enemy.kind.chase_speed))
+                },
+                EnemyState::Windup => {
+                    if enemy.state_time > enemy.kind.windup_time {
+                        enemy.enter(EnemyState::Attack);
+                        trigger = Some("attack");
+                        let (dx, dy) = (px - x, py - y);
+                        let length = (dx * dx + dy * dy).sqrt().max(1.0);
+                        flyer.velocity_x = dx / length * enemy.kind.attack_speed;
+                        flyer.velocity_y = dy / length * enemy.kind.attack_speed;
+                        None
+                    } else {
+                        // rear up before diving
+                        Some((x, py + flight.hover_height * 1.3, enemy.kind.chase_speed))
+                    }
+                },
+                EnemyState::Attack => {
+                    if enemy.state_time > enemy.kind.attack_time {
+                        enemy.enter(EnemyState::Cooldown);
+                    }
+                    None
+                },
+                EnemyState::Cooldown => {
+                    if enemy.state_time > enemy.kind.cooldown_time {
+                        enemy.enter(if seen { EnemyState::Chase } else { EnemyState::Patrol });
+                    }
+                    Some((x, py + flight.hover_height + bob, enemy.kind.patrol_speed))
+                },
+            };
+
+            if let Some((goal_x, goal_y, max_speed)) = goal {
+                let mut velocity_x = (goal_x - x) * STEERING;
+                let mut velocity_y = (goal_y - y) * STEERING;
+                let speed = (velocity_x * velocity_x + velocity_y * velocity_y).sqrt();
+                if speed > max_speed {
+                    velocity_x *= max_speed / speed;
+                    velocity_y *= max_speed / speed;
+                }
+                // ease out of swoops and knockback rather than stopping dead
+                let blend = 1.0 - (-STEERING * dt).exp();
+                flyer.velocity_x += (velocity_x - flyer.velocity_x) * blend;
+                flyer.velocity_y += (velocity_y - flyer.velocity_y) * blend;
+            }
+            transform.translate_x(flyer.velocity_x * dt);
+            transform.translate_y(flyer.velocity_y * dt);
+
+            let facing = if enemy.state == EnemyState::Patrol { flyer.velocity_x } else { px - x };
+            if facing < 0.0 {
+                flipped_components.insert(e, Flipped::Horizontal).unwrap();
+            } else if facing > 0.0 {
+                flipped_components.remove(e);
+            }
+
+            if let (Some(name), Some(animation)) = (trigger, sprite_animations.get_mut(e)) {
+                animation.trigger(name);
+            }
+        }
+    }
+}
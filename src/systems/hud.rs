@@ -0,0 +1,114 @@
+use amethyst::ecs::{Entities, Join, Read, ReadStorage, System, WriteStorage};
+use amethyst::renderer::ScreenDimensions;
+use amethyst::ui::{Anchor, UiText, UiTransform};
+
+use crate::states::HudElement;
+use crate::states::LetterboxBar;
+use crate::states::LetterboxSide;
+use crate::states::ViewSize;
+use crate::states::UI_HEIGHT;
+use crate::states::UI_WIDTH;
+
+// how far an element with this anchor moves to stay clear of the bars
+fn anchor_offset(anchor: &Anchor, bar_width: f32, bar_height: f32) -> (f32, f32) {
+    let x = match anchor {
+        Anchor::TopLeft | Anchor::MiddleLeft | Anchor::BottomLeft => bar_width,
+        Anchor::TopRight | Anchor::MiddleRight | Anchor::BottomRight => -bar_width,
+        _ => 0.0,
+    };
+    let y = match anchor {
+        Anchor::TopLeft | Anchor::TopMiddle | Anchor::TopRight => -bar_height,
+        Anchor::BottomLeft | Anchor::BottomMiddle | Anchor::BottomRight => bar_height,
+        _ => 0.0,
+    };
+    (x, y)
+}
+
+// Scales the HUD with the window and sizes the letterbox bars
+#[derive(Default)]
+pub struct HudLayoutSystem {
+    last_dimensions: (f32, f32),
+}
+
+impl<'s> System<'s> for HudLayoutSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, UiTransform>,
+        WriteStorage<'s, UiText>,
+        WriteStorage<'s, HudElement>,
+        ReadStorage<'s, LetterboxBar>,
+        Read<'s, ViewSize>,
+        Option<Read<'s, ScreenDimensions>>,
+    );
+
+    fn run (&mut self, (entities, mut ui_transforms, mut ui_texts, mut hud_elements,
+            letterbox_bars, view, screen_dimensions): Self::SystemData) {
+        let (width, height) = match screen_dimensions {
+            Some(dimensions) => (dimensions.width(), dimensions.height()),
+            None => return,
+        };
+
+        // remember how new elements were laid out at the design size
+        let mut added = false;
+        let mut new_elements = Vec::new();
+        for (e, transform, _, _) in
+            (&*entities, &ui_transforms, !&hud_elements, !&letterbox_bars).join() {
+            new_elements.push((e, HudElement {
+                x: transform.local_x,
+                y: transform.local_y,
+                width: transform.width,
+                height: transform.height,
+                font_size: ui_texts.get(e).map(|text| text.font_size),
+            }));
+        }
+        for (e, element) in new_elements {
+            hud_elements.insert(e, element).expect("Failed to add HudElement");
+            added = true;
+        }
+
+        if !added && self.last_dimensions == (width, height) {
+            return;
+        }
+        self.last_dimensions = (width, height);
+
+        // bars cover whatever the camera shows beyond the visible view
+        let bar_width = ((view.width - view.visible_width) * view.scale / 2.0).max(0.0);
+        let bar_height = ((view.height - view.visible_height) * view.scale / 2.0).max(0.0);
+
+        // lay the HUD out over the visible area, pulling elements anchored
+        // to an edge in past the bars
+        let ui_scale = ((width - bar_width * 2.0) / UI_WIDTH)
+            .min((height - bar_height * 2.0) / UI_HEIGHT);
+        for (e, transform, element) in (&*entities, &mut ui_transforms, &hud_elements).join() {
+            let (offset_x, offset_y) = anchor_offset(&transform.anchor, bar_width, bar_height);
+            transform.local_x = element.x * ui_scale + offset_x;
+            transform.local_y = element.y * ui_scale + offset_y;
+            transform.width = element.width * ui_scale;
+            transform.height = element.height * ui_scale;
+            if let (Some(text), Some(font_size)) = (ui_texts.get_mut(e), element.font_size) {
+                text.font_size = font_size * ui_scale;
+            }
+        }
+
+        for (bar, transform) in (&letterbox_bars, &mut ui_transforms).join() {
+            match bar.side {
+                LetterboxSide::Left | LetterboxSide::Right => {
+                    transform.width = bar_width;
+                    transform.height = height;
+                    transform.local_x = match bar.side {
+                        LetterboxSide::Left => bar_width / 2.0,
+                        _ => -bar_width / 2.0,
+                    };
+                },
+                LetterboxSide::Top | LetterboxSide::Bottom => {
+                    transform.width = width;
+                    transform.height = bar_height;
+                    transform.local_y = match bar.side {
+                        LetterboxSide::Bottom => bar_height / 2.0,
+                        _ => -bar_height / 2.0,
+                    };
+                },
+            }
+        }
+    }
+}
@@ -1,13 +1,41 @@
 mod player;
-mod mover;
+pub mod mover;
 mod animation;
-mod camera;
-mod potion;
+pub mod camera;
+pub mod potion;
+mod trajectory;
+pub mod cursor;
+mod sound;
+mod feedback;
+mod particles;
+mod brewing;
 pub mod enemy;
+mod hud;
+mod parallax;
+mod damage;
+mod projectile;
+mod flying;
+mod boss;
+mod spawner;
+mod waves;
 
 pub use self::player::PlayerSystem;
 pub use self::mover::MoverSystem;
 pub use self::animation::SpriteAnimationSystem;
 pub use self::camera::CameraSystem;
 pub use self::enemy::EnemySystem;
-pub use self::potion::PotionSystem;
\ No newline at end of file
+pub use self::potion::PotionSystem;
+pub use self::brewing::BrewingSystem;
+pub use self::trajectory::TrajectorySystem;
+pub use self::cursor::CursorSystem;
+pub use self::sound::AnimationSoundSystem;
+pub use self::feedback::DamageFeedbackSystem;
+pub use self::particles::{ParticleSystem, AnimationParticleSystem};
+pub use self::hud::HudLayoutSystem;
+pub use self::parallax::ParallaxSystem;
+pub use self::damage::DamageSystem;
+pub use self::projectile::ProjectileSystem;
+pub use self::flying::FlyingSystem;
+pub use self::boss::BossSystem;
+pub use self::spawner::SpawnerSystem;
+pub use self::waves::WaveSystem;
\ No newline at end of file
@@ -10,8 +10,40 @@ use crate::states::Collider;
 use crate::states::JumpState;
 
 const MAX_DROP_VELOCITY: f32 = 600.0;
-const GRAVITY: f32 = 400.0;
-const JUMP_VELOCITY: f32 = 250.0;
+pub const GRAVITY: f32 = 400.0;
+pub const JUMP_VELOCITY: f32 = 250.0;
+
+// Advance a mover by one time step, shared with the trajectory preview
+// so predicted arcs match what the potion actually does
+pub fn integrate(mover: &mut Mover, transform: &mut Transform, dt: f32) {
+    // translate distance
+    // fix for large time step since systems have variable time step
+    // we can warp through walls since we have primitive collision detection
+    // and don't force fixed update intervals (this'll do):
+    let dv = dt * -GRAVITY * mover.gravity;
+    let mut y_dist = 1.2 * mover.velocity_y * dt + 0.5 * dv * dt;
+    if (y_dist > crate::states::PLATFORM_HEIGHT / 2.0 ||
+        dt > 0.12) {
+        y_dist = 0.0; // don't support moving because we
+        // could still slide left if we are near edge of platform
+    }
+    transform.translate_y(y_dist);
+    transform.translate_x(mover.velocity_x * dt);
+
+    if transform.translation().x > mover.max_x {
+        transform.set_x(mover.max_x);
+    } else if transform.translation().x < mover.min_x {
+        transform.set_x(mover.min_x);
+    }
+
+    let mut new_velocity_y = mover.velocity_y + dv;
+
+    if new_velocity_y < -MAX_DROP_VELOCITY {
+        new_velocity_y = -MAX_DROP_VELOCITY;
+    }
+
+    mover.velocity_y = new_velocity_y;
+}
 
 pub struct MoverSystem {
 }
@@ -37,33 +69,15 @@ impl<'s> System<'s> for MoverSystem {
                 mover.jump_state = JumpState::Airborne;
             }
 
-            // translate distance
-            // fix for large time step since systems have variable time step
-            // we can warp through walls since we have primitive collision detection
-            // and don't force fixed update intervals (this'll do):
-            let dv = dt * -GRAVITY * mover.gravity;
-            let mut y_dist = 1.2 * mover.velocity_y * dt + 0.5 * dv * dt;
-            if (y_dist > crate::states::PLATFORM_HEIGHT / 2.0 ||
-                dt > 0.12) {
-                y_dist = 0.0; // don't support moving because we
-                // could still slide left if we are near edge of platform
-            }
-            transform.translate_y(y_dist);
-            transform.translate_x(mover.velocity_x * dt);
-
-            if transform.translation().x > mover.max_x {
-                transform.set_x(mover.max_x);
-            } else if transform.translation().x < mover.min_x {
-                transform.set_x(mover.min_x);
-            }
-
-            let mut new_velocity_y = mover.velocity_y + dv;
+            integrate(mover, transform, dt);
+        }
 
-            if new_velocity_y < -MAX_DROP_VELOCITY {
-                new_velocity_y = -MAX_DROP_VELOCITY;
+        // friction from the last frame's landing
+        for mover in (&mut movers).join() {
+            if let JumpState::Landed = mover.jump_state {
+                let slowed = (mover.velocity_x.abs() - mover.friction * dt).max(0.0);
+                mover.velocity_x = slowed * mover.velocity_x.signum();
             }
-
-            mover.velocity_y = new_velocity_y;
         }
 
         for (em, mover, mover_collider) in 
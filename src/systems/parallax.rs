@@ -0,0 +1,85 @@
+use amethyst::core::Transform;
+use amethyst::ecs::{Entities, Join, ReadStorage, System, WriteStorage};
+use amethyst::renderer::{Camera, Hidden, SpriteRender, Transparent};
+
+use crate::states::ParallaxLayer;
+
+// centre of the first tile and how many it takes to span the view
+// along one axis, or just the layer's own centre if it doesn't tile
+fn tile_span(origin: f32, size: f32, tiled: bool, view_centre: f32, view_size: f32) -> (f32, usize) {
+    if !tiled {
+        return (origin, 1);
+    }
+    let view_min = view_centre - view_size / 2.0;
+    let first_min = origin - size / 2.0 + ((view_min - (origin - size / 2.0)) / size).floor() * size;
+    let count = ((view_min + view_size - first_min) / size).ceil().max(1.0) as usize;
+    (first_min + size / 2.0, count)
+}
+
+pub struct ParallaxSystem {
+}
+
+impl<'s> System<'s> for ParallaxSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, ParallaxLayer>,
+        ReadStorage<'s, Camera>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Transparent>,
+        WriteStorage<'s, Hidden>,
+    );
+
+    fn run (&mut self, (entities, mut layers, cameras, mut transforms,
+            mut sprite_renders, mut transparents, mut hiddens): Self::SystemData) {
+        // the camera sits at the bottom left of an orthographic view
+        let view = (&cameras, &transforms).join()
+            .map(|(camera, transform)| {
+                let width = 2.0 / camera.proj[(0, 0)];
+                let height = 2.0 / camera.proj[(1, 1)];
+                let translation = transform.translation();
+                (translation.x + width / 2.0, translation.y + height / 2.0, width, height)
+            })
+            .next();
+        let (centre_x, centre_y, view_width, view_height) = match view {
+            Some(view) => view,
+            None => return,
+        };
+
+        for layer in (&mut layers).join() {
+            let origin_x = layer.offset.0 + centre_x * (1.0 - layer.scroll.0);
+            let origin_y = layer.offset.1 + centre_y * (1.0 - layer.scroll.1);
+            let (first_x, columns) = tile_span(origin_x, layer.width, layer.tile_x,
+                centre_x, view_width);
+            let (first_y, rows) = tile_span(origin_y, layer.height, layer.tile_y,
+                centre_y, view_height);
+
+            // grow the pool when the view gets bigger, never shrink it
+            while layer.tiles.len() < columns * rows {
+                let tile = entities.build_entity()
+                    .with(Transform::default(), &mut transforms)
+                    .with(SpriteRender {
+                        sprite_sheet: layer.sprite_sheet.clone(),
+                        sprite_number: 0,
+                    }, &mut sprite_renders)
+                    .with(Transparent, &mut transparents)
+                    .build();
+                layer.tiles.push(tile);
+            }
+
+            for (i, tile) in layer.tiles.iter().enumerate() {
+                if i >= columns * rows {
+                    hiddens.insert(*tile, Hidden).expect("Failed to hide tile");
+                    continue;
+                }
+                hiddens.remove(*tile);
+                if let Some(transform) = transforms.get_mut(*tile) {
+                    transform.set_xyz(
+                        first_x + (i % columns) as f32 * layer.width,
+                        first_y + (i / columns) as f32 * layer.height,
+                        layer.z);
+                }
+            }
+        }
+    }
+}
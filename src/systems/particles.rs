@@ -0,0 +1,158 @@
+use rand::Rng;
+
+use amethyst::{
+    core::timing::Time,
+    core::Transform,
+    ecs::{Entities, Join, Read, ReadExpect, ReadStorage, Resources, System, SystemData,
+        Write, WriteStorage},
+    renderer::{Rgba, SpriteRender, SpriteSheetHandle},
+    shrev::{EventChannel, ReaderId},
+};
+
+use crate::animations::AnimationEvent;
+use crate::spawn::{SpawnQueue, SpawnRequest};
+use crate::states::{Collider, Particle, ParticleEmitter};
+
+const PARTICLE_Z: f32 = 0.5;
+
+fn pick<R: Rng>(rng: &mut R, (low, high): (f32, f32)) -> f32 {
+    if high > low {
+        rng.gen_range(low, high)
+    } else {
+        low
+    }
+}
+
+// Emits and moves particles, drawn with the regular sprite pass
+pub struct ParticleSystem {
+}
+
+impl<'s> System<'s> for ParticleSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, ParticleEmitter>,
+        WriteStorage<'s, Particle>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Rgba>,
+        ReadExpect<'s, SpriteSheetHandle>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, (entities, mut emitters, mut particles, mut transforms,
+           mut sprite_renders, mut tints, sprite_sheet, time): Self::SystemData) {
+        let dt = time.delta_seconds();
+        let mut rng = rand::thread_rng();
+
+        // work out what each emitter releases this frame
+        let mut births = Vec::new();
+        let mut finished = Vec::new();
+        for (e, emitter, transform) in (&*entities, &mut emitters, &transforms).join() {
+            let mut count = 0;
+            if !emitter.burst_done {
+                count += emitter.burst;
+                emitter.burst_done = true;
+            }
+
+            emitter.elapsed_time += dt;
+            let emitting = emitter.duration < 0.0 || emitter.elapsed_time <= emitter.duration;
+            if emitting {
+                emitter.pending += emitter.rate * dt;
+                count += emitter.pending as usize;
+                emitter.pending = emitter.pending.fract();
+            } else {
+                finished.push(e);
+            }
+
+            let origin = transform.translation();
+            for _ in 0..count {
+                births.push((origin.x, origin.y, emitter.clone()));
+            }
+        }
+
+        for (x, y, emitter) in births {
+            let speed = pick(&mut rng, emitter.speed);
+            let angle = pick(&mut rng, emitter.angle).to_radians();
+            let offset_x = pick(&mut rng, (-emitter.spread_x, emitter.spread_x));
+
+            let mut transform = Transform::default();
+            transform.set_xyz(x + offset_x, y, PARTICLE_Z);
+            transform.set_scale(emitter.scale, emitter.scale, 1.0);
+
+            entities
+                .build_entity()
+                .with(transform, &mut transforms)
+                .with(SpriteRender {
+                    sprite_sheet: sprite_sheet.clone(),
+                    sprite_number: emitter.sprite,
+                }, &mut sprite_renders)
+                .with(emitter.tint, &mut tints)
+                .with(Particle {
+                    velocity_x: speed * angle.cos(),
+                    velocity_y: speed * angle.sin(),
+                    gravity: emitter.gravity,
+                    age: 0.0,
+                    lifetime: emitter.lifetime,
+                    alpha: emitter.tint.3,
+                }, &mut particles)
+                .build();
+        }
+
+        // effects that only exist to emit are removed once done,
+        // anything else just loses its emitter
+        for e in finished {
+            if sprite_renders.get(e).is_some() {
+                emitters.remove(e);
+            } else {
+                entities.delete(e).unwrap();
+            }
+        }
+
+        for (e, particle, transform, tint) in
+            (&*entities, &mut particles, &mut transforms, &mut tints).join() {
+            particle.age += dt;
+            if particle.age >= particle.lifetime {
+                entities.delete(e).unwrap();
+                continue;
+            }
+            particle.velocity_y -= particle.gravity * dt;
+            transform.translate_x(particle.velocity_x * dt);
+            transform.translate_y(particle.velocity_y * dt);
+            tint.3 = particle.alpha * (1.0 - particle.age / particle.lifetime);
+        }
+    }
+}
+
+// Kicks up dust keyed off animation events
+pub struct AnimationParticleSystem {
+    pub reader: Option<ReaderId<AnimationEvent>>,
+}
+
+impl<'s> System<'s> for AnimationParticleSystem {
+    type SystemData = (
+        Read<'s, EventChannel<AnimationEvent>>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Collider>,
+        Write<'s, SpawnQueue>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.reader = Some(res.fetch_mut::<EventChannel<AnimationEvent>>().register_reader());
+    }
+
+    fn run(&mut self, (animation_events, transforms, colliders, mut spawn_queue): Self::SystemData) {
+        for event in animation_events.read(self.reader.as_mut().unwrap()) {
+            match event.name.as_str() {
+                "land" => (),
+                _ => continue,
+            }
+            if let Some(transform) = transforms.get(event.entity) {
+                let half_height = colliders.get(event.entity).map_or(0.0, |c| c.height / 2.0);
+                let translation = transform.translation();
+                spawn_queue.push(SpawnRequest::at("landing_dust",
+                    translation.x, translation.y - half_height));
+            }
+        }
+    }
+}
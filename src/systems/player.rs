@@ -10,21 +10,20 @@ use amethyst::assets::AssetStorage;
 
 
 use crate::states::Player;
+use crate::states::Health;
 use crate::states::Mover;
 use crate::states::JumpState;
 use crate::states::Ingredient;
 use crate::states::Collider;
 use crate::states::UiEntities;
 use crate::states::UiValues;
-use crate::states::{PotionSpawner, PotionInfo};
+use crate::spawn::{SpawnQueue, SpawnRequest};
+use crate::systems::potion::throw_velocity;
 use crate::states::Gate;
 use crate::states::SoundEffects;
-
-pub const ATTACK_H_COST: usize = 3;
-pub const ATTACK_M_COST: usize = 1;
-pub const HEAL_H_COST: usize = 1;
-pub const HEAL_M_COST: usize = 3;
-pub const HEAL_AMOUNT: i32 = 10;
+use crate::states::Cursor;
+use crate::states::SpriteAnimation;
+use crate::recipes::{Recipes, RecipeEffect, Brewing};
 
 pub struct PlayerSystem {
     pub health_tick_rate: f32,
@@ -44,17 +43,23 @@ impl<'s> System<'s> for PlayerSystem {
         ReadExpect<'s, UiEntities>,
         Write<'s, UiValues>,
         WriteStorage<'s, UiText>,
-        Write<'s, PotionSpawner>,
+        Write<'s, SpawnQueue>,
         ReadStorage<'s, Gate>,
         Read<'s, AssetStorage<Source>>,
         ReadExpect<'s, SoundEffects>,
         Option<Read<'s, Output>>,
+        ReadExpect<'s, Recipes>,
+        Read<'s, Brewing>,
+        Read<'s, Cursor>,
+        WriteStorage<'s, SpriteAnimation>,
+        WriteStorage<'s, Health>,
     );
 
     fn run (&mut self, (mut movers, mut players, ingredients, 
             transforms, colliders, input, time, entities, ui_entities, mut ui_values,
-            mut ui_texts, mut potion_spawner, gates,
-            audio_source, sound_effects, audio_output
+            mut ui_texts, mut spawn_queue, gates,
+            audio_source, sound_effects, audio_output,
+            recipes, brewing, cursor, mut sprite_animations, mut healths,
             ): Self::SystemData) {
         
         let mut tick = false;
@@ -67,31 +72,30 @@ impl<'s> System<'s> for PlayerSystem {
             tick = true;
         }
         let mut lose = true;
-        for (ep, player, mover, player_collider, player_transform) in 
-            (&*entities, &mut players, &mut movers, &colliders, &transforms).join() {
+        for (ep, player, health, mover, player_collider, player_transform) in 
+            (&*entities, &mut players, &mut healths, &mut movers, &colliders, &transforms).join() {
             
             lose = false; // if a player exists we don't lose
 
             if tick {
-                player.health -= 1;
+                health.current -= 1;
             }
 
             if player_transform.translation().y < -50.0 {
-                player.health = 0;
+                health.current = 0;
             }
 
-            if player.health <= 0 {
-                player.health = 0;
-                lose = true;
+            if health.dead() {
+                health.current = 0;
+                player.dead = true;
             }
 
             // update timings:
-            player.last_hit += dt;
             player.last_throw += dt;
             player.last_heal += dt;
 
-            if player.health != ui_values.health {
-                ui_values.health = player.health;
+            if health.current != ui_values.health {
+                ui_values.health = health.current;
                 if let Some(text) = ui_texts.get_mut(ui_entities.health_entity) {
                     if ui_values.health <= 50 {
                         text.color = [0.8, 0.0, 0.0, 1.0];
@@ -103,10 +107,22 @@ impl<'s> System<'s> for PlayerSystem {
                 }
             }
 
+            // play out the death animation before removing the player
+            if player.dead {
+                lose = true;
+                player.death_time += dt;
+                player.aiming = false;
+                mover.velocity_x = 0.0;
+                if player.death_time > player.death_last {
+                    entities.delete(ep);
+                }
+                continue;
+            }
+
             // move
             let axis_value = input.axis_value("player");
             if player.in_hit {
-                if player.last_hit > player.hit_last {
+                if health.last_hit > player.hit_last {
                     player.in_hit = false;
                 }
             }
@@ -119,72 +135,91 @@ impl<'s> System<'s> for PlayerSystem {
                 // tell mover to jump
                 if let JumpState::Landed = mover.jump_state {
                     mover.jump_state = JumpState::Jump;
-                    if let Some(ref out_device) = audio_output.as_ref() {
-                        if let Some(sound) = audio_source.get(&sound_effects.jump) {
-                            out_device.play_once(sound, 0.2);
-                        }
+                    if let Some(animation) = sprite_animations.get_mut(ep) {
+                        animation.trigger("jump");
                     }
                 }
             }
 
-            // throw potions
+            // brew the active recipe, throwables aim while held
+            // and fly when the button is released. Aiming waits out the
+            // cooldown, so the preview only shows for a throw that will
+            // fire and a tap inside the cooldown does nothing
             let mut throw = false;
-            if input.action_is_down("throw").unwrap() {
-                if player.last_throw > player.throw_cooldown {
-                    if player.hornwort >= ATTACK_H_COST && 
-                        player.mushroom >= ATTACK_M_COST {
-
-                        player.hornwort -= ATTACK_H_COST;
-                        player.mushroom -= ATTACK_M_COST;
-
-                        if let Some(ref out_device) = audio_output.as_ref() {
-                            if let Some(sound) = audio_source.get(&sound_effects.potion_throw) {
-                                out_device.play_once(sound, 0.2);
-                            }
+            let throw_down = input.action_is_down("throw").unwrap();
+            let cooled_down = player.last_throw > player.throw_cooldown;
+            let released = player.aiming && !throw_down;
+            player.aiming = throw_down && cooled_down;
+            if cooled_down {
+                if let Some(recipe) = recipes.recipes.get(brewing.active) {
+                    let ready = match recipe.effect {
+                        RecipeEffect::Throw(_) => released,
+                        RecipeEffect::Heal(_) => throw_down,
+                    };
+                    if ready && recipe.affordable(player) {
+                        recipe.consume(player);
+
+                        match recipe.effect {
+                            RecipeEffect::Throw(ref damage) => {
+                                let ptrans = player_transform.translation();
+                                let (velocity_x, velocity_y) =
+                                    throw_velocity(ptrans.x, ptrans.y, cursor.x, cursor.y);
+
+                                // TODO tune spawn dist
+                                spawn_queue.push(SpawnRequest::at("potion", ptrans.x, ptrans.y)
+                                    .with_velocity(velocity_x, velocity_y)
+                                    .with_damage(damage.clone()));
+
+                                if let Some(animation) = sprite_animations.get_mut(ep) {
+                                    animation.trigger("throw");
+                                }
+                            },
+                            RecipeEffect::Heal(amount) => {
+                                if let Some(ref out_device) = audio_output.as_ref() {
+                                    if let Some(sound) = audio_source.get(&sound_effects.heal) {
+                                        out_device.play_once(sound, 0.2);
+                                    }
+                                }
+
+                                health.current = (health.current + amount).min(health.max);
+                                player.last_heal = 0.0;
+
+                                if let Some(animation) = sprite_animations.get_mut(ep) {
+                                    animation.trigger("heal");
+                                }
+                            },
                         }
 
-
-                        let ptrans = player_transform.translation();
-
-                        // grab mouse target
-                        let mouse_position = match input.mouse_position() {
-                            Some(pos) => pos,
-                            _ => (0.0, 0.0)
-                        };
-
-                        potion_spawner.potion = Some(
-                            PotionInfo {
-                                px: ptrans.x,
-                                py: ptrans.y,
-                                mx: mouse_position.0 as f32,
-                                my: mouse_position.1 as f32,
-                            }
-                        );
-
                         player.last_throw = 0.0;
                         throw = true;
-                    } 
+                    }
                 }
             }
 
+            // quick heal always drinks the first healing recipe
             if input.action_is_down("heal").unwrap() && 
                 player.last_heal > player.heal_cooldown {
-                if player.hornwort >= HEAL_H_COST && 
-                    player.mushroom >= HEAL_M_COST {
+                if let Some(recipe) = recipes.heal_recipe() {
+                    if recipe.affordable(player) {
+                        recipe.consume(player);
 
-                    player.hornwort -= HEAL_H_COST;
-                    player.mushroom -= HEAL_M_COST;
+                        if let Some(ref out_device) = audio_output.as_ref() {
+                            if let Some(sound) = audio_source.get(&sound_effects.heal) {
+                                out_device.play_once(sound, 0.2);
+                            }
+                        }
 
-                    if let Some(ref out_device) = audio_output.as_ref() {
-                        if let Some(sound) = audio_source.get(&sound_effects.heal) {
-                            out_device.play_once(sound, 0.2);
+                        if let RecipeEffect::Heal(amount) = recipe.effect {
+                            health.current = (health.current + amount).min(health.max);
                         }
-                    }
+                        player.last_heal = 0.0;
 
-                    player.health += HEAL_AMOUNT;
-                    player.last_heal = 0.0;
+                        if let Some(animation) = sprite_animations.get_mut(ep) {
+                            animation.trigger("heal");
+                        }
 
-                    throw = true;
+                        throw = true;
+                    }
                 }
             }
 
@@ -207,6 +242,10 @@ impl<'s> System<'s> for PlayerSystem {
                             }
                         }
 
+                        let i_translation = i_transform.translation();
+                        spawn_queue.push(SpawnRequest::at("pickup_sparkle",
+                            i_translation.x, i_translation.y));
+
                         match ingredient {
                             Ingredient::Hornwort{count} => {
                                 player.hornwort += count;
@@ -228,7 +267,7 @@ impl<'s> System<'s> for PlayerSystem {
                     &player_collider, 
                     &player_transform, 
                     &g_collider, 
-                    &g_transform) {
+                    &g_transform) && !gate.locked {
                     ui_values.win = true;
                 }
             }
@@ -245,7 +284,7 @@ impl<'s> System<'s> for PlayerSystem {
                 }
             }
 
-            if lose || ui_values.win {
+            if ui_values.win {
                 entities.delete(ep);
             }
         }
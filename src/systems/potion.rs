@@ -4,8 +4,9 @@ use amethyst::{
         Entities, ReadExpect, Write},
     audio::{output::Output, Source},
     assets::AssetStorage,
+    shrev::EventChannel,
 };
-use amethyst::ui::{UiText};
+use amethyst::core::nalgebra::Vector3;
 
 use crate::states::Enemy;
 use crate::states::Mover;
@@ -13,10 +14,22 @@ use crate::states::Platform;
 use crate::states::Collider;
 use crate::states::Potion;
 use crate::collision::check_collision;
-use crate::states::UiEntities;
-use crate::states::UiValues;
 use crate::states::SoundEffects;
+use crate::states::POTION_SPEED;
+use crate::spawn::{SpawnQueue, SpawnRequest};
+use crate::states::CameraEffects;
+use crate::damage::DamageEvent;
 
+// camera kick when a potion hits an enemy, and when it shatters on anything
+const HIT_TRAUMA: f32 = 0.4;
+const HIT_STOP: f32 = 0.05;
+const SHATTER_TRAUMA: f32 = 0.15;
+
+// Initial velocity of a potion thrown from (px, py) toward (tx, ty)
+pub fn throw_velocity(px: f32, py: f32, tx: f32, ty: f32) -> (f32, f32) {
+    let velocity = Vector3::new(tx - px, ty - py, 0.0).normalize();
+    (velocity.x * POTION_SPEED, velocity.y * POTION_SPEED)
+}
 
 pub struct PotionSystem {
 }
@@ -29,18 +42,18 @@ impl<'s> System<'s> for PotionSystem {
         ReadStorage<'s, Platform>,
         ReadStorage<'s, Potion>,
         ReadStorage<'s, Collider>,
-        ReadExpect<'s, UiEntities>,
-        Write<'s, UiValues>,
-        WriteStorage<'s, UiText>,
+        Write<'s, EventChannel<DamageEvent>>,
         Read<'s, AssetStorage<Source>>,
         ReadExpect<'s, SoundEffects>,
         Option<Read<'s, Output>>,
+        Write<'s, SpawnQueue>,
+        Write<'s, CameraEffects>,
     );
 
     fn run(&mut self, 
         (entities, transforms, enemies, platforms, potions, colliders,
-         ui_entities, mut ui_values, mut ui_texts,
-         audio_source, sound_effects, audio_output): Self::SystemData) {
+         mut damage_events, audio_source, sound_effects, audio_output, mut spawn_queue,
+         mut camera_effects): Self::SystemData) {
         //
         for (ep, potion, p_transform) in 
             (&* entities, &potions, &transforms).join() {
@@ -49,33 +62,43 @@ impl<'s> System<'s> for PotionSystem {
                 width: potion.width,
                 height: potion.height,
             };
+            let mut shattered = false;
             for (e, _enemy, e_transform, e_collider) in
                 (&*entities, &enemies, &transforms, &colliders).join() {
                 if (check_collision(&p_collider, &p_transform,
                     e_collider, e_transform)) {
-                    // update the score
-                    if let Some(text) = ui_texts.get_mut(ui_entities.score_entity) {
-                        ui_values.score += 1;
-                        text.text = format!("SCORE: {}", ui_values.score).to_string();
-                    }
-
                     if let Some(ref out_device) = audio_output.as_ref() {
                         if let Some(sound) = audio_source.get(&sound_effects.potion_hit) {
                             out_device.play_once(sound, 0.2);
                         }
                     }
 
+                    let translation = p_transform.translation();
+                    damage_events.single_write(DamageEvent {
+                        target: e,
+                        source_x: translation.x,
+                        source_y: translation.y,
+                        damage: potion.damage.clone(),
+                    });
+
+                    camera_effects.add_trauma(HIT_TRAUMA);
+                    camera_effects.hit_stop(HIT_STOP);
 
-                    entities.delete(e).unwrap();
-                    entities.delete(ep).unwrap();
+                    shattered = true;
+                    break;
                 }
             }
-            for (_platform, e_transform, e_collider) in
-                (&platforms, &transforms, &colliders).join() {
-                if (check_collision(&p_collider, &p_transform,
-                    e_collider, e_transform)) {
-                    entities.delete(ep).unwrap();
-                }
+            if !shattered {
+                shattered = (&platforms, &transforms, &colliders).join()
+                    .any(|(_platform, e_transform, e_collider)| {
+                        check_collision(&p_collider, &p_transform, e_collider, e_transform)
+                    });
+            }
+            if shattered {
+                let translation = p_transform.translation();
+                spawn_queue.push(SpawnRequest::at("potion_shatter", translation.x, translation.y));
+                camera_effects.add_trauma(SHATTER_TRAUMA);
+                entities.delete(ep).unwrap();
             }
         }
     }
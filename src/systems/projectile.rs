@@ -0,0 +1,80 @@
+use amethyst::{
+    core::Transform,
+    ecs::{Entities, Join, ReadExpect, ReadStorage, System, Write},
+    shrev::EventChannel,
+};
+
+use crate::collision::check_collision;
+use crate::damage::DamageEvent;
+use crate::spawn::{SpawnQueue, SpawnRequest};
+use crate::states::Collider;
+use crate::states::LevelBounds;
+use crate::states::Platform;
+use crate::states::Player;
+use crate::states::Projectile;
+
+// how far past the level a stray projectile flies before it's removed
+const OUT_OF_BOUNDS: f32 = 200.0;
+
+// Enemy projectiles, the counterpart of PotionSystem: they hurt the
+// player they touch and break on the first platform they hit
+pub struct ProjectileSystem {
+}
+
+impl<'s> System<'s> for ProjectileSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Projectile>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Platform>,
+        ReadStorage<'s, Collider>,
+        ReadExpect<'s, LevelBounds>,
+        Write<'s, EventChannel<DamageEvent>>,
+        Write<'s, SpawnQueue>,
+    );
+
+    fn run(&mut self, (entities, transforms, projectiles, players, platforms, colliders,
+           bounds, mut damage_events, mut spawn_queue): Self::SystemData) {
+        for (e, projectile, transform) in (&*entities, &projectiles, &transforms).join() {
+            let collider = Collider {
+                width: projectile.width,
+                height: projectile.height,
+            };
+            let translation = transform.translation();
+
+            let mut broken = false;
+            for (ep, _player, p_collider, p_transform) in
+                (&*entities, &players, &colliders, &transforms).join() {
+                if check_collision(&collider, transform, p_collider, p_transform) {
+                    damage_events.single_write(DamageEvent {
+                        target: ep,
+                        source_x: translation.x,
+                        source_y: translation.y,
+                        damage: projectile.damage.clone(),
+                    });
+                    broken = true;
+                    break;
+                }
+            }
+            if !broken {
+                broken = (&platforms, &colliders, &transforms).join()
+                    .any(|(_platform, p_collider, p_transform)| {
+                        check_collision(&collider, transform, p_collider, p_transform)
+                    });
+            }
+
+            if broken {
+                if let Some(ref splash) = projectile.splash {
+                    spawn_queue.push(SpawnRequest::at(splash, translation.x, translation.y));
+                }
+                entities.delete(e).unwrap();
+            } else if translation.x < bounds.min_x - OUT_OF_BOUNDS ||
+                translation.x > bounds.max_x + OUT_OF_BOUNDS ||
+                translation.y < bounds.min_y - OUT_OF_BOUNDS ||
+                translation.y > bounds.max_y + OUT_OF_BOUNDS {
+                entities.delete(e).unwrap();
+            }
+        }
+    }
+}
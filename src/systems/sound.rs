@@ -0,0 +1,44 @@
+use amethyst::{
+    assets::AssetStorage,
+    audio::{output::Output, Source},
+    ecs::{Read, ReadExpect, Resources, System, SystemData},
+    shrev::{EventChannel, ReaderId},
+};
+
+use crate::animations::AnimationEvent;
+use crate::states::SoundEffects;
+
+// Plays sounds keyed off animation frame events
+pub struct AnimationSoundSystem {
+    pub reader: Option<ReaderId<AnimationEvent>>,
+}
+
+impl<'s> System<'s> for AnimationSoundSystem {
+    type SystemData = (
+        Read<'s, EventChannel<AnimationEvent>>,
+        Read<'s, AssetStorage<Source>>,
+        ReadExpect<'s, SoundEffects>,
+        Option<Read<'s, Output>>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.reader = Some(res.fetch_mut::<EventChannel<AnimationEvent>>().register_reader());
+    }
+
+    fn run(&mut self, (animation_events, audio_source, sound_effects, audio_output): Self::SystemData) {
+        for event in animation_events.read(self.reader.as_mut().unwrap()) {
+            let sound = match event.name.as_str() {
+                "jump" => &sound_effects.jump,
+                "hurt" => &sound_effects.hurt,
+                "release" | "spit" => &sound_effects.potion_throw,
+                _ => continue,
+            };
+            if let Some(ref out_device) = audio_output.as_ref() {
+                if let Some(sound) = audio_source.get(sound) {
+                    out_device.play_once(sound, 0.2);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use rand::Rng;
+
+use amethyst::{
+    core::timing::Time,
+    core::Transform,
+    ecs::{Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage},
+};
+
+use crate::biomes::Biome;
+use crate::spawn::{SpawnQueue, SpawnRequest};
+use crate::states::Player;
+use crate::states::SpawnedBy;
+use crate::states::Spawner;
+
+// Spawners make one of their prefabs every cooldown while the player
+// is in range, until they have max_alive out or their budget runs out.
+// The level's biome can swap what they make for its own enemies
+pub struct SpawnerSystem {
+}
+
+impl<'s> System<'s> for SpawnerSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, Spawner>,
+        ReadStorage<'s, SpawnedBy>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Transform>,
+        Write<'s, SpawnQueue>,
+        ReadExpect<'s, Biome>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, (entities, mut spawners, spawned, players, transforms,
+           mut spawn_queue, biome, time): Self::SystemData) {
+        let dt = time.delta_seconds();
+        let mut rng = rand::thread_rng();
+
+        let target = (&players, &transforms).join()
+            .filter(|(player, _)| !player.dead)
+            .map(|(_, transform)| (transform.translation().x, transform.translation().y))
+            .next();
+
+        for (e, spawner, transform) in (&*entities, &mut spawners, &transforms).join() {
+            spawner.elapsed += dt;
+            let (x, y) = (transform.translation().x, transform.translation().y);
+
+            let awake = spawner.range <= 0.0 || target.map_or(false, |(px, py)|
+                (px - x) * (px - x) + (py - y) * (py - y) < spawner.range * spawner.range);
+            let alive = (&spawned).join().filter(|spawned| spawned.spawner == e).count();
+            if !awake || alive >= spawner.max_alive || spawner.budget == Some(0) ||
+               spawner.elapsed < spawner.cooldown || spawner.prefabs.is_empty() {
+                continue;
+            }
+
+            spawner.elapsed = 0.0;
+            spawner.budget = spawner.budget.map(|budget| budget - 1);
+            let prefab = biome.spawn(&spawner.prefabs[rng.gen_range(0, spawner.prefabs.len())]);
+            let mut request = SpawnRequest::at(prefab, x, y).with_owner(e);
+            if let Some((min_x, max_x)) = spawner.bounds {
+                request = request.with_bounds(min_x, max_x);
+            }
+            spawn_queue.push(request);
+            spawn_queue.push(SpawnRequest::at("enemy_puff", x, y));
+        }
+    }
+}
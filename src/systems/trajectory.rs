@@ -0,0 +1,104 @@
+use amethyst::core::Transform;
+use amethyst::ecs::{Join, Read, ReadExpect, ReadStorage, System, WriteStorage};
+use amethyst::renderer::Hidden;
+
+use crate::collision::check_collision;
+use crate::recipes::{Brewing, RecipeEffect, Recipes};
+use crate::states::{Collider, Cursor, Mover, Platform, Player, TrajectoryDots};
+use crate::prefabs::Prefabs;
+use crate::states::LEVEL_WIDTH;
+use crate::systems::mover::integrate;
+use crate::systems::potion::throw_velocity;
+
+// fixed step for the preview, the real potion uses frame time
+// but the arc is close enough at normal frame rates
+const PREVIEW_STEP: f32 = 1.0 / 60.0;
+const STEPS_PER_DOT: usize = 4;
+const MAX_PREVIEW_STEPS: usize = 120;
+
+pub struct TrajectorySystem {
+}
+
+impl<'s> System<'s> for TrajectorySystem {
+    type SystemData = (
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Platform>,
+        ReadStorage<'s, Collider>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Hidden>,
+        Read<'s, Cursor>,
+        ReadExpect<'s, Recipes>,
+        Read<'s, Brewing>,
+        ReadExpect<'s, TrajectoryDots>,
+        ReadExpect<'s, Prefabs>,
+    );
+
+    fn run(&mut self, (players, platforms, colliders, mut transforms, mut hiddens,
+           cursor, recipes, brewing, trajectory_dots, prefabs): Self::SystemData) {
+        let throwing = match recipes.recipes.get(brewing.active) {
+            Some(recipe) => match recipe.effect {
+                RecipeEffect::Throw(_) => true,
+                _ => false,
+            },
+            None => false,
+        };
+
+        let mut start = None;
+        for (player, transform) in (&players, &transforms).join() {
+            if player.aiming && throwing {
+                start = Some((transform.translation().x, transform.translation().y));
+            }
+        }
+
+        // simulate the throw and collect dot positions up to the first platform
+        let mut positions = Vec::new();
+        if let Some((px, py)) = start {
+            let (velocity_x, velocity_y) = throw_velocity(px, py, cursor.x, cursor.y);
+
+            // mirror what spawning the potion prefab would build
+            let potion = prefabs.get("potion");
+            let mut mover = Mover::new(-100.0, LEVEL_WIDTH + 100.0);
+            mover.velocity_x = velocity_x;
+            mover.velocity_y = velocity_y;
+            mover.gravity = potion.mover.as_ref().map_or(1.0, |mover| mover.gravity);
+            let mut simulated = Transform::default();
+            simulated.set_xyz(px, py, 0.0);
+            let collider = match potion.potion {
+                Some(ref size) => Collider { width: size.width, height: size.height },
+                None => Collider { width: 0.0, height: 0.0 },
+            };
+
+            for step in 1..=MAX_PREVIEW_STEPS {
+                integrate(&mut mover, &mut simulated, PREVIEW_STEP);
+
+                let hit = (&platforms, &colliders, &transforms).join()
+                    .any(|(_platform, p_collider, p_transform)| {
+                        check_collision(&collider, &simulated, p_collider, p_transform)
+                    });
+                if hit {
+                    break;
+                }
+
+                if step % STEPS_PER_DOT == 0 {
+                    let translation = simulated.translation();
+                    positions.push((translation.x, translation.y));
+                }
+            }
+        }
+
+        for (i, dot) in trajectory_dots.dots.iter().enumerate() {
+            match positions.get(i) {
+                Some((x, y)) => {
+                    if let Some(transform) = transforms.get_mut(*dot) {
+                        transform.set_x(*x);
+                        transform.set_y(*y);
+                    }
+                    hiddens.remove(*dot);
+                },
+                None => {
+                    hiddens.insert(*dot, Hidden).unwrap();
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use rand::Rng;
+
+use amethyst::{
+    core::timing::Time,
+    ecs::{Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage},
+    ui::UiText,
+};
+
+use crate::navigation::NavGraph;
+use crate::spawn::{SpawnQueue, SpawnRequest};
+use crate::states::SpawnedBy;
+use crate::states::Spawner;
+use crate::states::UiEntities;
+use crate::states::WaveState;
+use crate::waves::Waves;
+
+// how high over a platform supplies are dropped from
+const SUPPLY_HEIGHT: f32 = 30.0;
+
+// Wave mode: alternates breaks, where supplies are dropped, with waves
+// where every spawner is handed the wave's enemies. Does nothing when
+// no Waves were loaded
+pub struct WaveSystem {
+}
+
+impl<'s> System<'s> for WaveSystem {
+    type SystemData = (
+        WriteStorage<'s, Spawner>,
+        ReadStorage<'s, SpawnedBy>,
+        WriteStorage<'s, UiText>,
+        Option<Read<'s, Waves>>,
+        Write<'s, WaveState>,
+        Write<'s, SpawnQueue>,
+        ReadExpect<'s, NavGraph>,
+        ReadExpect<'s, UiEntities>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, (mut spawners, spawned, mut ui_texts, waves, mut state,
+           mut spawn_queue, navigation, ui_entities, time): Self::SystemData) {
+        let waves = match waves {
+            Some(waves) => waves,
+            None => return,
+        };
+        let mut rng = rand::thread_rng();
+
+        let label = if state.fighting {
+            let cleared = (&spawners).join().all(|spawner| spawner.budget == Some(0)) &&
+                (&spawned).join().next().is_none();
+            if cleared {
+                state.fighting = false;
+                state.wave += 1;
+                state.break_time = 0.0;
+                state.restocked = false;
+            }
+            format!("WAVE {}", state.wave + 1)
+        } else {
+            // restock once at the start of each break
+            if !state.restocked && !navigation.nodes.is_empty() {
+                state.restocked = true;
+                for drop in waves.supplies.iter() {
+                    for _ in 0..drop.count {
+                        if rng.gen::<f32>() >= drop.chance {
+                            continue;
+                        }
+                        let node = &navigation.nodes[rng.gen_range(0, navigation.nodes.len())];
+                        let x = rng.gen_range(node.left, node.right);
+                        spawn_queue.push(SpawnRequest::at(&drop.prefab, x, node.top + SUPPLY_HEIGHT));
+                    }
+                }
+            }
+            state.break_time += time.delta_seconds();
+
+            if state.break_time < waves.break_time {
+                for spawner in (&mut spawners).join() {
+                    spawner.budget = Some(0);
+                }
+                format!("WAVE {} IN {}", state.wave + 1,
+                    (waves.break_time - state.break_time).ceil())
+            } else {
+                let wave = waves.wave(state.wave);
+                for spawner in (&mut spawners).join() {
+                    spawner.prefabs = wave.prefabs.clone();
+                    spawner.max_alive = wave.max_alive;
+                    spawner.cooldown = wave.cooldown;
+                    spawner.budget = Some(wave.count);
+                    spawner.range = 0.0;
+                    spawner.elapsed = wave.cooldown; // first one straight away
+                }
+                state.fighting = true;
+                format!("WAVE {}", state.wave + 1)
+            }
+        };
+
+        if let Some(text) = ui_texts.get_mut(ui_entities.wave_entity) {
+            text.text = label;
+        }
+    }
+}
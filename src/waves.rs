@@ -0,0 +1,52 @@
+use amethyst::config::Config;
+use amethyst::utils::application_root_dir;
+use serde::{Deserialize, Serialize};
+
+use crate::enemies::LootDrop;
+
+// What every spawner does during one wave
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Wave {
+    pub count: usize, // enemies from each spawner
+    pub max_alive: usize, // per spawner
+    pub cooldown: f32,
+    pub prefabs: Vec<String>,
+}
+
+// Loaded from resources/waves.ron, only in GameMode::Waves
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Waves {
+    pub break_time: f32, // breather before each wave
+    // dropped on random platforms during each break
+    pub supplies: Vec<LootDrop>,
+    pub waves: Vec<Wave>,
+    // the last wave repeats with this many more enemies each time
+    pub extra_per_wave: usize,
+}
+
+impl Waves {
+    pub fn load() -> Waves {
+        let path = format!("{}/resources/waves.ron", application_root_dir());
+        let waves = Waves::load_no_fallback(&path)
+            .unwrap_or_else(|e| panic!("Failed to load waves {}: {}", path, e));
+        // the last wave repeats forever, so there has to be one
+        if waves.waves.is_empty() {
+            panic!("Failed to load waves {}: no waves listed", path);
+        }
+        waves
+    }
+
+    // waves are numbered from 0
+    pub fn wave(&self, number: usize) -> Wave {
+        let last = self.waves.len() - 1;
+        let mut wave = self.waves[number.min(last)].clone();
+        wave.count += number.saturating_sub(last) * self.extra_per_wave;
+        wave
+    }
+
+    pub fn spawned_prefabs(&self) -> Vec<&String> {
+        self.waves.iter().flat_map(|wave| wave.prefabs.iter())
+            .chain(self.supplies.iter().map(|drop| &drop.prefab))
+            .collect()
+    }
+}